@@ -0,0 +1,192 @@
+//! Zip archives of files and folders, for the "Compress" command.
+//!
+//! Like every other operation, reading the items and writing the archive go through
+//! a [`FileSystem`], so the jail and [`crate::MemoryFs`] apply here too.
+
+use std::io::{self, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{Datelike, Timelike};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::{FsError, IoResultExt, Result};
+use crate::fs::{Entry, FileSystem};
+use crate::names;
+use crate::ops::{self, Progress};
+
+/// Where the archive of `items` goes: next to them, named after the item when there
+/// is only one (`photos` → `photos.zip`), `Archive.zip` otherwise. A name that's
+/// taken gets a number, like "Keep Both" does.
+pub fn archive_path(fs: &dyn FileSystem, items: &[PathBuf]) -> Option<PathBuf> {
+    let first = items.first()?;
+    let dir = first.parent()?;
+    let name = match items {
+        [only] => {
+            let mut name = only.file_name()?.to_os_string();
+            name.push(".zip");
+            name
+        }
+        _ => "Archive.zip".into(),
+    };
+    let path = dir.join(name);
+    Some(if fs.metadata(&path).is_ok() { names::keep_both_name(fs, &path) } else { path })
+}
+
+/// Packs `items` into a new zip archive at `to`: files as they are, folders with
+/// everything inside, and links as links rather than what they point to. Each item
+/// goes in under its own name, so they should come from the same folder.
+///
+/// The archive is put together in memory and written in one go, so a failure leaves
+/// nothing behind at `to`. Refuses to replace anything already there.
+pub fn compress(fs: &dyn FileSystem, items: &[PathBuf], to: &Path, progress: &Progress) -> Result<()> {
+    if fs.metadata(to).is_ok() {
+        return Err(FsError::AlreadyExists(to.to_path_buf()));
+    }
+    let mut entries = Vec::new();
+    for item in items {
+        let entry = fs.metadata(item).at(item)?;
+        progress.add_total(ops::count_items(fs, &entry));
+        entries.push(entry);
+    }
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for entry in &entries {
+        let name = entry.path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        add(fs, &mut zip, entry, &name, to, progress)?;
+    }
+    let archive = zip.finish().map_err(|e| zip_error(to, e))?.into_inner();
+    fs.write(to, &archive).at(to)
+}
+
+// Adds `entry` as `name`, and a folder's contents below it
+fn add(
+    fs: &dyn FileSystem,
+    zip: &mut ZipWriter<Cursor<Vec<u8>>>,
+    entry: &Entry,
+    name: &str,
+    archive: &Path,
+    progress: &Progress,
+) -> Result<()> {
+    let from = entry.path.as_path();
+    let mut options = SimpleFileOptions::default().large_file(entry.len >= u64::from(u32::MAX));
+    if let Some(mode) = entry.mode {
+        options = options.unix_permissions(mode);
+    }
+    if let Some(modified) = entry.modified.and_then(zip_time) {
+        options = options.last_modified_time(modified);
+    }
+
+    if entry.is_symlink {
+        let target = fs.read_link(from).at(from)?;
+        zip.add_symlink(name, target.to_string_lossy(), options).map_err(|e| zip_error(archive, e))?;
+    } else if entry.is_dir {
+        zip.add_directory(name, options).map_err(|e| zip_error(archive, e))?;
+        for child in fs.read_dir(from).at(from)? {
+            let child_name = format!("{}/{}", name, child.name());
+            add(fs, zip, &child, &child_name, archive, progress)?;
+        }
+    } else {
+        let content = fs.read(from).at(from)?;
+        zip.start_file(name, options.compression_method(CompressionMethod::Deflated)).map_err(|e| zip_error(archive, e))?;
+        zip.write_all(&content).at(archive)?;
+    }
+    progress.add_done();
+    Ok(())
+}
+
+// Zip's errors are mostly I/O ones; the rest mean the archive couldn't be put together
+fn zip_error(archive: &Path, e: ZipError) -> FsError {
+    match e {
+        ZipError::Io(e) => FsError::from_io(archive, e),
+        e => FsError::Io { path: archive.to_path_buf(), source: io::Error::other(e) },
+    }
+}
+
+// Zip stores local time to the second, and only from 1980 on
+fn zip_time(modified: SystemTime) -> Option<zip::DateTime> {
+    let local = chrono::DateTime::<chrono::Local>::from(modified).naive_local();
+    zip::DateTime::from_date_and_time(
+        u16::try_from(local.year()).ok()?,
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::MemoryFs;
+    use std::io::Read;
+
+    // A `root` folder holding `a/x.txt` and `b.txt`
+    fn sample() -> MemoryFs {
+        let fs = MemoryFs::new();
+        ops::create_folder(&fs, Path::new(""), "root").unwrap();
+        ops::create_folder(&fs, Path::new("root"), "a").unwrap();
+        ops::create_file(&fs, Path::new("root/a"), "x.txt", "x").unwrap();
+        ops::create_file(&fs, Path::new("root"), "b.txt", "b").unwrap();
+        fs
+    }
+
+    fn unzip(fs: &MemoryFs, archive: &Path) -> Vec<(String, String)> {
+        let mut zip = zip::ZipArchive::new(Cursor::new(fs.read(archive).unwrap())).unwrap();
+        let mut files = Vec::new();
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i).unwrap();
+            let mut content = String::new();
+            file.read_to_string(&mut content).unwrap();
+            files.push((file.name().to_string(), content));
+        }
+        files
+    }
+
+    #[test]
+    fn compress_packs_folders_with_everything_inside() {
+        let fs = sample();
+        let items = [PathBuf::from("root/a"), PathBuf::from("root/b.txt")];
+        let progress = Progress::default();
+        compress(&fs, &items, Path::new("root/Archive.zip"), &progress).unwrap();
+
+        let files = unzip(&fs, Path::new("root/Archive.zip"));
+        let expected = [("a/", ""), ("a/x.txt", "x"), ("b.txt", "b")];
+        assert_eq!(files, expected.map(|(name, content)| (name.to_string(), content.to_string())));
+        assert_eq!((progress.total(), progress.done()), (3, 3));
+    }
+
+    #[test]
+    fn compress_refuses_to_replace_anything() {
+        let fs = sample();
+        let result = compress(&fs, &[PathBuf::from("root/a")], Path::new("root/b.txt"), &Progress::default());
+        assert!(matches!(result, Err(FsError::AlreadyExists(_))));
+        assert_eq!(fs.read(Path::new("root/b.txt")).unwrap(), b"b");
+    }
+
+    #[test]
+    fn a_missing_item_leaves_no_archive_behind() {
+        let fs = sample();
+        let items = [PathBuf::from("root/b.txt"), PathBuf::from("root/gone.txt")];
+        let result = compress(&fs, &items, Path::new("root/Archive.zip"), &Progress::default());
+        assert!(matches!(result, Err(FsError::NotFound(_))));
+        assert!(fs.metadata(Path::new("root/Archive.zip")).is_err());
+    }
+
+    #[test]
+    fn archives_are_named_after_a_single_item() {
+        let fs = sample();
+        assert_eq!(archive_path(&fs, &[PathBuf::from("root/a")]), Some(PathBuf::from("root/a.zip")));
+        assert_eq!(archive_path(&fs, &[PathBuf::from("root/b.txt")]), Some(PathBuf::from("root/b.txt.zip")));
+        let both = [PathBuf::from("root/a"), PathBuf::from("root/b.txt")];
+        assert_eq!(archive_path(&fs, &both), Some(PathBuf::from("root/Archive.zip")));
+
+        ops::create_file(&fs, Path::new("root"), "Archive.zip", "").unwrap();
+        assert_eq!(archive_path(&fs, &both), Some(PathBuf::from("root/Archive (2).zip")));
+        assert_eq!(archive_path(&fs, &[]), None);
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::jail::OutsideJail;
+
+/// What went wrong with a file operation, and on which path, in terms the UI can show as-is.
+#[derive(Debug)]
+pub enum FsError {
+    NotFound(PathBuf),
+    AlreadyExists(PathBuf),
+    /// EACCES / EPERM.
+    PermissionDenied(PathBuf),
+    /// ENOSPC, or a quota was exceeded.
+    NoSpace(PathBuf),
+    /// The file was opened as text but isn't UTF-8.
+    InvalidUtf8(PathBuf),
+    /// The item would have to be moved to another file system (EXDEV).
+    CrossesDevices(PathBuf),
+    /// A folder was to be copied or moved into itself or one of its subfolders.
+    IntoItself(PathBuf),
+    /// A [`crate::JailedFs`] refused a path outside its root.
+    OutsideJail(PathBuf),
+    /// Anything else, with the original error.
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// `Result` with an [`FsError`].
+pub type Result<T> = std::result::Result<T, FsError>;
+
+impl FsError {
+    /// Classifies an `io::Error` that happened while working on `path`.
+    pub fn from_io(path: &Path, source: io::Error) -> Self {
+        let path = path.to_path_buf();
+        match source.kind() {
+            io::ErrorKind::NotFound => FsError::NotFound(path),
+            io::ErrorKind::AlreadyExists => FsError::AlreadyExists(path),
+            io::ErrorKind::PermissionDenied if source.get_ref().is_some_and(|inner| inner.is::<OutsideJail>()) => {
+                FsError::OutsideJail(path)
+            }
+            io::ErrorKind::PermissionDenied => FsError::PermissionDenied(path),
+            io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => FsError::NoSpace(path),
+            io::ErrorKind::CrossesDevices => FsError::CrossesDevices(path),
+            _ => FsError::Io { path, source },
+        }
+    }
+
+    /// The path the operation failed on.
+    pub fn path(&self) -> &Path {
+        match self {
+            FsError::NotFound(path)
+            | FsError::AlreadyExists(path)
+            | FsError::PermissionDenied(path)
+            | FsError::NoSpace(path)
+            | FsError::InvalidUtf8(path)
+            | FsError::CrossesDevices(path)
+            | FsError::IntoItself(path)
+            | FsError::OutsideJail(path)
+            | FsError::Io { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for FsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsError::NotFound(path) => write!(f, "{} does not exist", path.display()),
+            FsError::AlreadyExists(path) => write!(f, "{} already exists", path.display()),
+            FsError::PermissionDenied(path) => write!(f, "Permission denied: {}", path.display()),
+            FsError::NoSpace(path) => write!(f, "No space left on the drive while writing {}", path.display()),
+            FsError::InvalidUtf8(path) => write!(f, "{} is not valid UTF-8 text", path.display()),
+            FsError::CrossesDevices(path) => write!(f, "{} is on another drive", path.display()),
+            FsError::IntoItself(path) => write!(f, "Can't put {} inside itself", path.display()),
+            FsError::OutsideJail(path) => write!(f, "{} is outside the folder this file manager is limited to", path.display()),
+            FsError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for FsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FsError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Attaches the path to an `io::Result`, e.g. `fs.create_dir(&path).at(&path)?`.
+pub trait IoResultExt<T> {
+    fn at(self, path: &Path) -> Result<T>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn at(self, path: &Path) -> Result<T> {
+        self.map_err(|e| FsError::from_io(path, e))
+    }
+}
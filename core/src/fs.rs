@@ -0,0 +1,481 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::{self, File, FileTimes};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// One item of a directory listing, with the metadata the UI needs so it
+/// never has to stat the path again while drawing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Whether `path` itself is a symbolic link. The other fields describe what it
+    /// points to, so a link to a folder opens like one; a broken link is a file.
+    pub is_symlink: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// Unix permission bits (`0o755`), or `None` where there are none to show.
+    pub mode: Option<u32>,
+}
+
+impl Entry {
+    /// The last path component, for display.
+    pub fn name(&self) -> Cow<'_, str> {
+        self.path.file_name().unwrap_or_default().to_string_lossy()
+    }
+}
+
+/// The storage primitives the file manager needs. Every operation in
+/// [`crate::ops`] goes through this trait instead of touching `std::fs` directly.
+pub trait FileSystem: Send + Sync {
+    /// Creates an empty file, truncating it if it already exists.
+    fn create_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates a single directory. The parent must already exist.
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates a directory and any missing parents. Succeeds if it already exists.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Replaces the whole content of a file, creating it if needed.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Reads the whole content of a file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Removes a single file.
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes a directory and everything below it.
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Moves a file or folder, replacing `to` if it is a file.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Copies a file with its permissions and timestamps, replacing `to`.
+    fn copy_file(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Gives `to` the permissions and timestamps of `from`. Used on folders once
+    /// their content has been copied, since adding it changes their timestamps.
+    fn copy_attributes(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Lists the files and folders directly inside a directory, in no particular order.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>>;
+
+    /// Looks up a single file or folder, following symlinks but noting in
+    /// [`Entry::is_symlink`] that `path` is one.
+    fn metadata(&self, path: &Path) -> io::Result<Entry>;
+
+    /// Where a symbolic link points, exactly as stored in the link.
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Creates a symbolic link at `link` pointing to `target`, which is stored as given.
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()>;
+
+    /// The absolute path with `.`, `..` and symlinks resolved. The path must exist.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// The real disk, backed by `std::fs`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalFs;
+
+impl FileSystem for LocalFs {
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        File::create(path).map(|_| ())
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut file = File::create(path)?; // Open the file for writing
+        file.write_all(contents)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut file = File::open(path)?; // Open the file for reading
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::copy(from, to)?; // Copies the permissions, but not the timestamps
+        self.copy_attributes(from, to)
+    }
+
+    fn copy_attributes(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let metadata = fs::metadata(from)?;
+        let mut times = FileTimes::new();
+        if let Ok(accessed) = metadata.accessed() {
+            times = times.set_accessed(accessed);
+        }
+        if let Ok(modified) = metadata.modified() {
+            times = times.set_modified(modified);
+        }
+        // Timestamps first: a read-only copy can still have its times set by its owner
+        File::open(to)?.set_times(times)?;
+        fs::set_permissions(to, metadata.permissions())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let entries = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| self.metadata(&entry.path()).ok()) // Skip anything we can't stat (e.g. sockets)
+            .collect();
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Entry> {
+        let link = fs::symlink_metadata(path)?;
+        let is_symlink = link.is_symlink();
+        // Links are described by their target like `Path::is_dir` does, broken ones by the link itself
+        let metadata = if is_symlink { fs::metadata(path).unwrap_or(link) } else { link };
+        if !metadata.is_file() && !metadata.is_dir() && !metadata.is_symlink() {
+            return Err(io::Error::other(format!(
+                "{} is not a regular file or folder",
+                path.display()
+            )));
+        }
+        Ok(Entry {
+            path: path.to_path_buf(),
+            is_dir: metadata.is_dir(),
+            is_symlink,
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            created: metadata.created().ok(),  // Not every file system records it
+            mode: mode(&metadata),
+        })
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    #[cfg(unix)]
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        std::os::unix::fs::symlink(target, link)
+    }
+
+    // Windows needs to know up front whether the target is a folder, and the rights to make links
+    #[cfg(not(unix))]
+    fn symlink(&self, _target: &Path, link: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("can't create the symbolic link {}", link.display()),
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn mode(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// A throwaway file system kept entirely in memory, for tests and previews.
+///
+/// Paths are normalized lexically (`.` is dropped, `..` pops a component), and
+/// the empty path is treated as an always-present root directory.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn nodes(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        // A panic while holding the lock can't leave the map half-updated, so keep going
+        self.nodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn normalize(path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                other => normalized.push(other),
+            }
+        }
+        normalized
+    }
+
+    // The root and anything stored as a `Node::Dir` count as directories
+    fn is_dir(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> bool {
+        path.as_os_str().is_empty() || matches!(nodes.get(path), Some(Node::Dir))
+    }
+
+    fn entry(path: &Path, node: &Node) -> Entry {
+        Entry {
+            path: path.to_path_buf(),
+            is_dir: matches!(node, Node::Dir),
+            is_symlink: false,
+            len: match node {
+                Node::File(contents) => contents.len() as u64,
+                Node::Dir => 0,
+            },
+            modified: None,
+            created: None,
+            mode: None,
+        }
+    }
+
+    fn check_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> io::Result<()> {
+        let parent = path.parent().unwrap_or(Path::new(""));
+        if Self::is_dir(nodes, parent) {
+            Ok(())
+        } else {
+            Err(not_found(parent))
+        }
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.write(path, &[])
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        let path = Self::normalize(path);
+        let mut nodes = self.nodes();
+        if path.as_os_str().is_empty() || nodes.contains_key(&path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        Self::check_parent(&nodes, &path)?;
+        nodes.insert(path, Node::Dir);
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = Self::normalize(path);
+        let mut nodes = self.nodes();
+        let mut current = PathBuf::new();
+        for component in path.components() {
+            current.push(component);
+            match nodes.get(&current) {
+                Some(Node::Dir) => {}
+                Some(Node::File(_)) => return Err(not_a_directory(&current)),
+                None => {
+                    nodes.insert(current.clone(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let path = Self::normalize(path);
+        let mut nodes = self.nodes();
+        if Self::is_dir(&nodes, &path) {
+            return Err(is_a_directory(&path));
+        }
+        Self::check_parent(&nodes, &path)?;
+        nodes.insert(path, Node::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let path = Self::normalize(path);
+        match self.nodes().get(&path) {
+            Some(Node::File(contents)) => Ok(contents.clone()),
+            Some(Node::Dir) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = Self::normalize(path);
+        let mut nodes = self.nodes();
+        match nodes.get(&path) {
+            Some(Node::File(_)) => {
+                nodes.remove(&path);
+                Ok(())
+            }
+            Some(Node::Dir) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = Self::normalize(path);
+        let mut nodes = self.nodes();
+        if !matches!(nodes.get(&path), Some(Node::Dir)) {
+            return Err(not_found(&path));
+        }
+        // `starts_with` is component-wise, so `a/bc` is not removed along with `a/b`
+        nodes.retain(|node_path, _| !node_path.starts_with(&path));
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let from = Self::normalize(from);
+        let to = Self::normalize(to);
+        let mut nodes = self.nodes();
+        let Some(node) = nodes.get(&from).cloned() else {
+            return Err(not_found(&from));
+        };
+        if from == to {
+            return Ok(());
+        }
+        if to.starts_with(&from) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("can't move {} into itself", from.display()),
+            ));
+        }
+        Self::check_parent(&nodes, &to)?;
+        match (&node, nodes.get(&to)) {
+            (_, None) | (Node::File(_), Some(Node::File(_))) => {}
+            (Node::File(_), Some(Node::Dir)) => return Err(is_a_directory(&to)),
+            (Node::Dir, Some(Node::File(_))) => return Err(not_a_directory(&to)),
+            (Node::Dir, Some(Node::Dir)) => {
+                // Like rename(2), an empty directory may be replaced
+                if nodes.keys().any(|path| path.parent() == Some(to.as_path())) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::DirectoryNotEmpty,
+                        format!("{} is not empty", to.display()),
+                    ));
+                }
+            }
+        }
+
+        // Re-key the node and everything below it
+        let moved: Vec<PathBuf> = nodes.keys().filter(|path| path.starts_with(&from)).cloned().collect();
+        for old_path in moved {
+            if let Some(node) = nodes.remove(&old_path) {
+                let new_path = match old_path.strip_prefix(&from) {
+                    Ok(suffix) if !suffix.as_os_str().is_empty() => to.join(suffix),
+                    _ => to.clone(),
+                };
+                nodes.insert(new_path, node);
+            }
+        }
+        Ok(())
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let content = self.read(from)?;
+        self.write(to, &content)
+    }
+
+    // Nodes have no permissions or timestamps, so there is nothing to copy
+    fn copy_attributes(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.metadata(from)?;
+        self.metadata(to).map(|_| ())
+    }
+
+    // Entries keep the caller's spelling of the path (e.g. `./root/a`), like `std::fs::read_dir`
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        let normalized = Self::normalize(path);
+        let nodes = self.nodes();
+        if !Self::is_dir(&nodes, &normalized) {
+            return Err(not_found(&normalized));
+        }
+        let entries = nodes
+            .iter()
+            .filter(|(node_path, _)| node_path.parent() == Some(normalized.as_path()))
+            .filter_map(|(node_path, node)| {
+                let name = node_path.file_name()?;
+                Some(Self::entry(&path.join(name), node))
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Entry> {
+        let normalized = Self::normalize(path);
+        match self.nodes().get(&normalized) {
+            Some(node) => Ok(Self::entry(path, node)),
+            None if normalized.as_os_str().is_empty() => Ok(Self::entry(path, &Node::Dir)),
+            None => Err(not_found(&normalized)),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.metadata(path)?;  // There are no links, just check it's there
+        Ok(Self::normalize(path))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        let normalized = Self::normalize(path);
+        self.metadata(path)?;
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a symbolic link", normalized.display()),
+        ))
+    }
+
+    fn symlink(&self, _target: &Path, link: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("can't create the symbolic link {} in memory", Self::normalize(link).display()),
+        ))
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} does not exist", path.display()),
+    )
+}
+
+fn is_a_directory(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::IsADirectory,
+        format!("{} is a directory", path.display()),
+    )
+}
+
+fn not_a_directory(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotADirectory,
+        format!("{} is not a directory", path.display()),
+    )
+}
@@ -0,0 +1,290 @@
+//! Confining every file operation to one folder and what's below it.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::fs::{Entry, FileSystem};
+use crate::paths;
+
+/// The error inside the `io::Error` a [`JailedFs`] refuses with, so [`crate::FsError`] can tell it apart.
+#[derive(Debug)]
+pub struct OutsideJail {
+    pub path: PathBuf,
+    pub root: PathBuf,
+}
+
+impl fmt::Display for OutsideJail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is outside {}", self.path.display(), self.root.display())
+    }
+}
+
+impl std::error::Error for OutsideJail {}
+
+/// A [`FileSystem`] that refuses to touch anything outside `root`.
+///
+/// Paths are resolved before they're checked: `..` is followed, and so are symlinks,
+/// so a link inside the root pointing elsewhere doesn't get anyone out. Removing or
+/// renaming looks at the link itself, though, since that never touches its target.
+/// In a listing, a link out of the root shows up like a broken one.
+#[derive(Debug)]
+pub struct JailedFs<F> {
+    inner: F,
+    root: PathBuf,
+}
+
+impl<F: FileSystem> JailedFs<F> {
+    /// If `root` can't be resolved (e.g. it doesn't exist yet) it's kept as given,
+    /// which still refuses everything outside it.
+    pub fn new(inner: F, root: &Path) -> Self {
+        let root = inner
+            .canonicalize(root)
+            .or_else(|_| paths::normalize(root))
+            .unwrap_or_else(|_| root.to_path_buf());
+        Self { inner, root }
+    }
+
+    /// The folder everything is kept inside, with symlinks resolved.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Fails unless `path`, with symlinks followed, is the root or below it.
+    pub fn check(&self, path: &Path) -> io::Result<()> {
+        self.check_resolved(path, true)
+    }
+
+    /// Like [`JailedFs::check`], but a symlink at `path` itself isn't followed.
+    fn check_entry(&self, path: &Path) -> io::Result<()> {
+        self.check_resolved(path, false)
+    }
+
+    fn check_resolved(&self, path: &Path, follow: bool) -> io::Result<()> {
+        let normalized = paths::normalize(path)?;
+        let (mut existing, last) = match (follow, normalized.parent(), normalized.file_name()) {
+            (false, Some(parent), Some(name)) => (parent, Some(name)),
+            _ => (normalized.as_path(), None),
+        };
+
+        // Resolve the deepest part that exists; what's missing below it can't be a link yet
+        let mut missing = Vec::new();
+        let mut resolved = loop {
+            match self.inner.canonicalize(existing) {
+                Ok(resolved) => break resolved,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                        return Err(e);
+                    };
+                    missing.push(name);
+                    existing = parent;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+        resolved.extend(missing.into_iter().rev().chain(last));
+
+        if resolved.starts_with(&self.root) {
+            Ok(())
+        } else {
+            let outside = OutsideJail { path: path.to_path_buf(), root: self.root.clone() };
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, outside))
+        }
+    }
+}
+
+impl<F: FileSystem> FileSystem for JailedFs<F> {
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.create_file(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.create_dir(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.check(path)?;
+        self.inner.write(path, contents)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.check(path)?;
+        self.inner.read(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.check_entry(path)?;
+        self.inner.remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.check_entry(path)?;
+        self.inner.remove_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.check_entry(from)?;
+        self.check_entry(to)?;
+        self.inner.rename(from, to)
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.check(from)?;
+        self.check(to)?;
+        self.inner.copy_file(from, to)
+    }
+
+    fn copy_attributes(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.check(from)?;
+        self.check(to)?;
+        self.inner.copy_attributes(from, to)
+    }
+
+    // A link out of the root is listed like a broken one, so nothing about its target shows
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<Entry>> {
+        self.check(path)?;
+        let mut entries = self.inner.read_dir(path)?;
+        for entry in entries.iter_mut().filter(|entry| entry.is_symlink) {
+            if self.check(&entry.path).is_err() {
+                *entry = Entry {
+                    path: std::mem::take(&mut entry.path),
+                    is_dir: false,
+                    is_symlink: true,
+                    len: 0,
+                    modified: None,
+                    created: None,
+                    mode: None,
+                };
+            }
+        }
+        Ok(entries)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Entry> {
+        self.check(path)?;
+        self.inner.metadata(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.check(path)?;
+        self.inner.canonicalize(path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.check_entry(path)?;
+        self.inner.read_link(path)
+    }
+
+    // Only where the link goes is checked; following it later is checked like any other path
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.check_entry(link)?;
+        self.inner.symlink(target, link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::LocalFs;
+    use crate::testing::TempDir;
+
+    // A jail at `root` with `root/inside.txt`, next to an `outside.txt` it must not reach
+    fn sample() -> (TempDir, JailedFs<LocalFs>) {
+        let dir = TempDir::new();
+        std::fs::create_dir(dir.path().join("root")).unwrap();
+        std::fs::write(dir.path().join("root/inside.txt"), "in").unwrap();
+        std::fs::write(dir.path().join("outside.txt"), "out").unwrap();
+        let jail = JailedFs::new(LocalFs, &dir.path().join("root"));
+        (dir, jail)
+    }
+
+    fn refused<T: fmt::Debug>(result: io::Result<T>) -> bool {
+        result.unwrap_err().get_ref().is_some_and(|inner| inner.is::<OutsideJail>())
+    }
+
+    #[test]
+    fn dot_dot_doesnt_get_out() {
+        let (_dir, jail) = sample();
+        let root = jail.root().to_path_buf();
+        jail.create_dir(&root.join("sub")).unwrap();
+        assert_eq!(jail.read(&root.join("sub/../inside.txt")).unwrap(), b"in");
+
+        assert!(refused(jail.read(&root.join("../outside.txt"))));
+        assert!(refused(jail.metadata(&root.join(".."))));
+        assert!(refused(jail.read_dir(&root.join("inside.txt/../.."))));
+        assert!(refused(jail.write(&root.join("../outside.txt"), b"changed")));
+        assert_eq!(std::fs::read(root.join("../outside.txt")).unwrap(), b"out");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_out_of_the_root_are_not_followed() {
+        let (dir, jail) = sample();
+        let root = jail.root().to_path_buf();
+        let link = root.join("link.txt");
+        std::os::unix::fs::symlink(dir.path().join("outside.txt"), &link).unwrap();
+
+        assert!(refused(jail.read(&link)));
+        assert!(refused(jail.metadata(&link)));
+        assert!(refused(jail.write(&link, b"changed")));
+        assert_eq!(std::fs::read(dir.path().join("outside.txt")).unwrap(), b"out");
+
+        // Listed, but like a broken link
+        let listed = jail.read_dir(&root).unwrap();
+        let entry = listed.iter().find(|entry| entry.path == link).unwrap();
+        assert!(entry.is_symlink && !entry.is_dir);
+        assert_eq!((entry.len, entry.modified, entry.mode), (0, None, None));
+
+        // The link itself is inside, so it can still go
+        jail.remove_file(&link).unwrap();
+        assert!(dir.path().join("outside.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_within_the_root_are_followed() {
+        let (_dir, jail) = sample();
+        let root = jail.root().to_path_buf();
+        std::os::unix::fs::symlink(root.join("inside.txt"), root.join("link.txt")).unwrap();
+
+        assert_eq!(jail.read(&root.join("link.txt")).unwrap(), b"in");
+        let listed = jail.read_dir(&root).unwrap();
+        let entry = listed.iter().find(|entry| entry.path == root.join("link.txt")).unwrap();
+        assert_eq!(entry.len, 2);
+    }
+
+    #[test]
+    fn missing_folders_are_checked_by_where_they_would_be() {
+        let (dir, jail) = sample();
+        let root = jail.root().to_path_buf();
+        jail.create_dir_all(&root.join("a/b/c")).unwrap();
+        assert!(root.join("a/b/c").is_dir());
+
+        assert!(refused(jail.create_dir_all(&dir.path().join("elsewhere/a"))));
+        assert!(refused(jail.create_dir_all(&root.join("missing/../../elsewhere"))));
+        assert!(!dir.path().join("elsewhere").exists());
+    }
+
+    #[test]
+    fn nothing_is_moved_across_the_root() {
+        let (dir, jail) = sample();
+        let root = jail.root().to_path_buf();
+
+        assert!(refused(jail.rename(&root.join("inside.txt"), &dir.path().join("moved.txt"))));
+        assert!(refused(jail.rename(&root.join("inside.txt"), &root.join("../moved.txt"))));
+        assert!(refused(jail.rename(&dir.path().join("outside.txt"), &root.join("moved.txt"))));
+        assert!(refused(jail.copy_file(&root.join("inside.txt"), &dir.path().join("copied.txt"))));
+        assert!(root.join("inside.txt").exists() && dir.path().join("outside.txt").exists());
+        assert!(!dir.path().join("moved.txt").exists() && !root.join("moved.txt").exists());
+
+        jail.rename(&root.join("inside.txt"), &root.join("moved.txt")).unwrap();
+        assert_eq!(std::fs::read(root.join("moved.txt")).unwrap(), b"in");
+    }
+}
@@ -0,0 +1,375 @@
+//! An undo/redo history of file operations.
+//!
+//! Every operation is performed through the [`Journal`], which records enough to
+//! invert it: deletes go to the trash instead of being removed, and saves keep a
+//! backup of the content before and after, so both directions can be replayed.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime};
+
+use crate::error::{FsError, IoResultExt, Result};
+use crate::fs::FileSystem;
+use crate::ops;
+use crate::trash::{Trash, TrashItem};
+
+/// A recorded operation and whatever it needs to be undone and redone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// `trashed` holds the file while the creation is undone.
+    CreateFile { path: PathBuf, trashed: Option<TrashItem> },
+    /// `trashed` holds the folder while the creation is undone.
+    CreateFolder { path: PathBuf, trashed: Option<TrashItem> },
+    /// `before` and `after` are backups of the content on either side of the save.
+    Save { path: PathBuf, before: PathBuf, after: PathBuf },
+    Rename { from: PathBuf, to: PathBuf },
+    Move { from: PathBuf, to: PathBuf },
+    /// `trashed` holds the copy while the copy is undone.
+    Copy { from: PathBuf, to: PathBuf, trashed: Option<TrashItem> },
+    /// `trashed` holds the item while the delete is in effect.
+    Delete { path: PathBuf, trashed: Option<TrashItem> },
+    /// `trashed` holds the archive while the compression is undone.
+    Compress { items: Vec<PathBuf>, to: PathBuf, trashed: Option<TrashItem> },
+}
+
+impl Operation {
+    /// A short description for the history list, e.g. `Rename a.txt → b.txt`.
+    pub fn describe(&self) -> String {
+        let name = |path: &Path| path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+        match self {
+            Operation::CreateFile { path, .. } => format!("Create file {}", name(path)),
+            Operation::CreateFolder { path, .. } => format!("Create folder {}", name(path)),
+            Operation::Save { path, .. } => format!("Save {}", name(path)),
+            Operation::Rename { from, to } => format!("Rename {} → {}", name(from), name(to)),
+            Operation::Move { from, to } => format!("Move {} → {}", name(from), to.display()),
+            Operation::Copy { from, to, .. } => format!("Copy {} → {}", name(from), to.display()),
+            Operation::Delete { path, .. } => format!("Delete {}", name(path)),
+            Operation::Compress { items, to, .. } => match items.as_slice() {
+                [only] => format!("Compress {} → {}", name(only), name(to)),
+                _ => format!("Compress {} items → {}", items.len(), name(to)),
+            },
+        }
+    }
+}
+
+/// One entry of the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub operation: Operation,
+    pub done_at: NaiveDateTime,
+}
+
+/// The history itself. Records before [`Journal::applied`] are in effect and can be
+/// undone; the ones after it were undone and can be redone until a new operation
+/// replaces them.
+#[derive(Debug)]
+pub struct Journal {
+    trash: Trash,
+    backup_dir: PathBuf,
+    records: Vec<Record>,
+    applied: usize,
+    next_backup: u64,
+}
+
+impl Journal {
+    /// `backup_dir` is where saved-over content is kept; it is created on first use.
+    pub fn new(trash: Trash, backup_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            trash,
+            backup_dir: backup_dir.into(),
+            records: Vec::new(),
+            applied: 0,
+            next_backup: 0,
+        }
+    }
+
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// How many records, from the start, are currently in effect.
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.applied > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.applied < self.records.len()
+    }
+
+    /// Adds an operation that was already performed. Anything that was undone is
+    /// dropped, as it can no longer be redone on top of the new state.
+    pub fn record(&mut self, fs: &dyn FileSystem, operation: Operation) {
+        for discarded in self.records.drain(self.applied..) {
+            if let Operation::Save { before, after, .. } = discarded.operation {
+                let _ = fs.remove_file(&before);
+                let _ = fs.remove_file(&after);
+            }
+        }
+        self.records.push(Record {
+            operation,
+            done_at: Local::now().naive_local(),
+        });
+        self.applied = self.records.len();
+    }
+
+    pub fn create_file(&mut self, fs: &dyn FileSystem, dir: &Path, file_name: &str, content: &str) -> Result<PathBuf> {
+        let path = ops::create_file(fs, dir, file_name, content)?;
+        self.record(fs, Operation::CreateFile { path: path.clone(), trashed: None });
+        Ok(path)
+    }
+
+    pub fn create_folder(&mut self, fs: &dyn FileSystem, dir: &Path, folder_name: &str) -> Result<PathBuf> {
+        let path = ops::create_folder(fs, dir, folder_name)?;
+        self.record(fs, Operation::CreateFolder { path: path.clone(), trashed: None });
+        Ok(path)
+    }
+
+    /// Saves `content` to `path`, keeping the old content so the save can be undone.
+    pub fn save(&mut self, fs: &dyn FileSystem, path: &Path, content: &str) -> Result<()> {
+        let old = match fs.read(path) {
+            Ok(old) => old,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(FsError::from_io(path, e)),
+        };
+        fs.create_dir_all(&self.backup_dir).at(&self.backup_dir)?;
+        let before = self.backup_path();
+        let after = self.backup_path();
+        fs.write(&before, &old).at(&before)?;
+        fs.write(&after, content.as_bytes()).at(&after)?;
+
+        if let Err(e) = ops::save_content(fs, content, path) {
+            let _ = fs.remove_file(&before);
+            let _ = fs.remove_file(&after);
+            return Err(e);
+        }
+        self.record(fs, Operation::Save { path: path.to_path_buf(), before, after });
+        Ok(())
+    }
+
+    /// Deletes the saved-over content kept for undoing saves, for when the session
+    /// ends. Saves recorded so far can't be undone or redone after this.
+    pub fn remove_backups(&self, fs: &dyn FileSystem) -> Result<()> {
+        match fs.remove_dir_all(&self.backup_dir) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()), // Nothing was ever saved
+            Err(e) => Err(FsError::from_io(&self.backup_dir, e)),
+        }
+    }
+
+    pub fn rename(&mut self, fs: &dyn FileSystem, from: &Path, to: &Path) -> Result<()> {
+        ops::rename(fs, from, to)?;
+        self.record(fs, Operation::Rename { from: from.to_path_buf(), to: to.to_path_buf() });
+        Ok(())
+    }
+
+    pub fn move_item(&mut self, fs: &dyn FileSystem, from: &Path, to: &Path) -> Result<()> {
+        ops::move_item(fs, from, to)?;
+        self.record(fs, Operation::Move { from: from.to_path_buf(), to: to.to_path_buf() });
+        Ok(())
+    }
+
+    /// Copies a file or folder to `to`, which must not exist yet.
+    pub fn copy(&mut self, fs: &dyn FileSystem, from: &Path, to: &Path) -> Result<()> {
+        ops::copy(fs, from, to)?;
+        let (from, to) = (from.to_path_buf(), to.to_path_buf());
+        self.record(fs, Operation::Copy { from, to, trashed: None });
+        Ok(())
+    }
+
+    /// Moves `path` to the trash.
+    pub fn delete(&mut self, fs: &dyn FileSystem, path: &Path) -> Result<()> {
+        let item = self.trash.put(fs, path)?;
+        self.record(fs, Operation::Delete { path: path.to_path_buf(), trashed: Some(item) });
+        Ok(())
+    }
+
+    /// Reverts the most recent operation still in effect. Returns `false` if there was none.
+    /// On failure the history is left as it was, so the undo can be retried.
+    pub fn undo(&mut self, fs: &dyn FileSystem) -> Result<bool> {
+        if !self.can_undo() {
+            return Ok(false);
+        }
+        let mut record = self.records[self.applied - 1].clone();
+        self.invert(fs, &mut record.operation)?;
+        self.applied -= 1;
+        self.records[self.applied] = record;
+        Ok(true)
+    }
+
+    /// Performs the most recently undone operation again. Returns `false` if there was none.
+    pub fn redo(&mut self, fs: &dyn FileSystem) -> Result<bool> {
+        if !self.can_redo() {
+            return Ok(false);
+        }
+        let mut record = self.records[self.applied].clone();
+        self.replay(fs, &mut record.operation)?;
+        self.records[self.applied] = record;
+        self.applied += 1;
+        Ok(true)
+    }
+
+    /// Undoes or redoes until exactly `applied` records are in effect, stopping at the first failure.
+    pub fn jump_to(&mut self, fs: &dyn FileSystem, applied: usize) -> Result<()> {
+        let applied = applied.min(self.records.len());
+        while self.applied > applied {
+            self.undo(fs)?;
+        }
+        while self.applied < applied {
+            self.redo(fs)?;
+        }
+        Ok(())
+    }
+
+    fn invert(&self, fs: &dyn FileSystem, operation: &mut Operation) -> Result<()> {
+        match operation {
+            // Undoing a creation trashes rather than deletes, in case something was put inside since
+            Operation::CreateFile { path, trashed }
+            | Operation::CreateFolder { path, trashed }
+            | Operation::Copy { to: path, trashed, .. }
+            | Operation::Compress { to: path, trashed, .. } => {
+                *trashed = Some(self.trash.put(fs, path)?);
+            }
+            Operation::Save { path, before, .. } => restore_backup(fs, before, path)?,
+            Operation::Rename { from, to } => ops::rename(fs, to, from)?,
+            Operation::Move { from, to } => ops::move_item(fs, to, from)?,
+            Operation::Delete { path, trashed } => {
+                let item = trashed.as_ref().ok_or_else(|| missing_from_trash(path))?;
+                self.trash.restore(fs, item)?;
+                *trashed = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn replay(&self, fs: &dyn FileSystem, operation: &mut Operation) -> Result<()> {
+        match operation {
+            Operation::CreateFile { path, trashed }
+            | Operation::CreateFolder { path, trashed }
+            | Operation::Copy { to: path, trashed, .. }
+            | Operation::Compress { to: path, trashed, .. } => {
+                let item = trashed.take().ok_or_else(|| missing_from_trash(path))?;
+                if let Err(e) = self.trash.restore(fs, &item) {
+                    *trashed = Some(item);
+                    return Err(e);
+                }
+            }
+            Operation::Save { path, after, .. } => restore_backup(fs, after, path)?,
+            Operation::Rename { from, to } => ops::rename(fs, from, to)?,
+            Operation::Move { from, to } => ops::move_item(fs, from, to)?,
+            Operation::Delete { path, trashed } => {
+                *trashed = Some(self.trash.put(fs, path)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn backup_path(&mut self) -> PathBuf {
+        self.next_backup += 1;
+        self.backup_dir.join(format!("{}.bak", self.next_backup))
+    }
+}
+
+fn restore_backup(fs: &dyn FileSystem, backup: &Path, path: &Path) -> Result<()> {
+    let content = fs.read(backup).at(backup)?;
+    fs.write(path, &content).at(path)
+}
+
+// The trashed copy was purged or restored behind the journal's back
+fn missing_from_trash(path: &Path) -> FsError {
+    FsError::NotFound(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{LocalFs, MemoryFs};
+    use crate::testing::TempDir;
+
+    fn journal() -> Journal {
+        Journal::new(Trash::new("trash"), "backups")
+    }
+
+    #[test]
+    fn save_is_undone_and_redone() {
+        let fs = MemoryFs::new();
+        let mut journal = journal();
+        let path = Path::new("notes.txt");
+        fs.write(path, b"old").unwrap();
+        journal.save(&fs, path, "new").unwrap();
+
+        assert!(journal.undo(&fs).unwrap());
+        assert_eq!(fs.read(path).unwrap(), b"old");
+        assert!(journal.redo(&fs).unwrap());
+        assert_eq!(fs.read(path).unwrap(), b"new");
+        assert!(!journal.redo(&fs).unwrap());
+    }
+
+    #[test]
+    fn backups_are_removed_at_the_end() {
+        let fs = MemoryFs::new();
+        let mut journal = journal();
+        journal.save(&fs, Path::new("notes.txt"), "new").unwrap();
+        assert!(fs.metadata(Path::new("backups")).is_ok());
+
+        journal.remove_backups(&fs).unwrap();
+        assert!(fs.metadata(Path::new("backups")).is_err());
+        journal.remove_backups(&fs).unwrap();
+    }
+
+    #[test]
+    fn rename_and_move_are_undone_in_reverse_order() {
+        let fs = MemoryFs::new();
+        let mut journal = journal();
+        fs.create_dir(Path::new("dir")).unwrap();
+        fs.write(Path::new("a.txt"), b"a").unwrap();
+        journal.rename(&fs, Path::new("a.txt"), Path::new("b.txt")).unwrap();
+        journal.move_item(&fs, Path::new("b.txt"), Path::new("dir/b.txt")).unwrap();
+
+        journal.jump_to(&fs, 0).unwrap();
+        assert_eq!(fs.read(Path::new("a.txt")).unwrap(), b"a");
+        assert!(fs.metadata(Path::new("dir/b.txt")).is_err());
+        assert!(!journal.can_undo());
+
+        journal.jump_to(&fs, 2).unwrap();
+        assert_eq!(fs.read(Path::new("dir/b.txt")).unwrap(), b"a");
+        assert!(fs.metadata(Path::new("a.txt")).is_err());
+    }
+
+    #[test]
+    fn a_new_operation_drops_what_was_undone() {
+        let fs = MemoryFs::new();
+        let mut journal = journal();
+        fs.write(Path::new("a.txt"), b"a").unwrap();
+        journal.rename(&fs, Path::new("a.txt"), Path::new("b.txt")).unwrap();
+        journal.undo(&fs).unwrap();
+        assert!(journal.can_redo());
+
+        journal.rename(&fs, Path::new("a.txt"), Path::new("c.txt")).unwrap();
+        assert!(!journal.can_redo());
+        assert_eq!(journal.records().len(), 1);
+        assert_eq!(journal.records()[0].operation.describe(), "Rename a.txt → c.txt");
+    }
+
+    #[test]
+    fn creating_and_deleting_go_through_the_trash() {
+        let dir = TempDir::new();
+        let fs = LocalFs;
+        let mut journal = Journal::new(Trash::new(dir.path().join("trash")), dir.path().join("backups"));
+
+        let path = journal.create_file(&fs, dir.path(), "a.txt", "hello").unwrap();
+        journal.undo(&fs).unwrap();
+        assert!(!path.exists());
+        journal.redo(&fs).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+
+        journal.delete(&fs, &path).unwrap();
+        assert!(!path.exists());
+        journal.undo(&fs).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+    }
+}
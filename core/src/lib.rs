@@ -0,0 +1,28 @@
+//! Headless file operations used by the file manager UI.
+//!
+//! Nothing in here depends on Bevy or egui, so the operations can be reused
+//! from other tools and exercised against [`MemoryFs`] without opening a window.
+
+pub mod archive;
+pub mod error;
+pub mod fs;
+pub mod jail;
+pub mod journal;
+pub mod names;
+pub mod ops;
+pub mod paths;
+pub mod templates;
+pub mod thumbnails;
+pub mod trash;
+
+#[cfg(test)]
+mod testing;
+
+pub use error::{FsError, IoResultExt};
+pub use fs::{Entry, FileSystem, LocalFs, MemoryFs};
+pub use jail::JailedFs;
+pub use journal::{Journal, Operation, Record};
+pub use names::NameError;
+pub use templates::Template;
+pub use thumbnails::{ThumbnailCache, ThumbnailSize};
+pub use trash::{Trash, TrashItem};
@@ -0,0 +1,219 @@
+//! Checks for names typed by the user before anything is created or renamed.
+//!
+//! Names are held to the portable subset that works on Linux, macOS and Windows,
+//! so a folder can be copied to any of them without surprises.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::fs::FileSystem;
+
+// Device names Windows refuses as file names, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+const ILLEGAL_CHARS: &[char] = &['/', '\\', '<', '>', ':', '"', '|', '?', '*'];
+const MAX_NAME_BYTES: usize = 255;
+
+/// Why a name can't be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    /// `.` and `..` mean the current and parent folder.
+    DotName,
+    IllegalChar(char),
+    Reserved(String),
+    TrailingDotOrSpace,
+    TooLong,
+    /// Something with that name is already in the folder.
+    Exists(PathBuf),
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "Enter a name"),
+            NameError::DotName => write!(f, "\".\" and \"..\" can't be used as names"),
+            NameError::IllegalChar(c) if c.is_control() => write!(f, "Names can't contain control characters"),
+            NameError::IllegalChar(c) => write!(f, "Names can't contain \"{}\"", c),
+            NameError::Reserved(name) => write!(f, "\"{}\" is a reserved name", name),
+            NameError::TrailingDotOrSpace => write!(f, "Names can't end with a dot or a space"),
+            NameError::TooLong => write!(f, "Names can be at most {} bytes long", MAX_NAME_BYTES),
+            NameError::Exists(path) => write!(f, "{} already exists", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// Checks a single file or folder name, without looking at the disk.
+pub fn validate_name(name: &str) -> Result<(), NameError> {
+    if name.trim().is_empty() {
+        return Err(NameError::Empty);
+    }
+    if name == "." || name == ".." {
+        return Err(NameError::DotName);
+    }
+    if let Some(c) = name.chars().find(|c| ILLEGAL_CHARS.contains(c) || c.is_control()) {
+        return Err(NameError::IllegalChar(c));
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Err(NameError::TrailingDotOrSpace);
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return Err(NameError::TooLong);
+    }
+    // `nul.txt` is just as reserved as `NUL`
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if let Some(reserved) = RESERVED_NAMES.iter().find(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        return Err(NameError::Reserved(reserved.to_string()));
+    }
+    Ok(())
+}
+
+/// Checks `name` and that nothing called that exists in `dir` yet.
+/// Returns the path the new item would get.
+pub fn check_new_name(fs: &dyn FileSystem, dir: &Path, name: &str) -> Result<PathBuf, NameError> {
+    validate_name(name)?;
+    let path = dir.join(name);
+    if fs.metadata(&path).is_ok() {
+        return Err(NameError::Exists(path));
+    }
+    Ok(path)
+}
+
+/// Checks renaming `from` to `new_name` in the same folder. Returns the new path,
+/// which is `from` itself when the name didn't change.
+///
+/// Case-only changes (`notes.txt` → `Notes.txt`) are allowed even where the drive
+/// treats both spellings as the same file.
+pub fn check_rename(fs: &dyn FileSystem, from: &Path, new_name: &str) -> Result<PathBuf, NameError> {
+    validate_name(new_name)?;
+    let to = from.with_file_name(new_name);
+    if to == from {
+        return Ok(to);
+    }
+    let taken = if is_case_only_change(from, &to) {
+        exists_exactly(fs, &to)
+    } else {
+        fs.metadata(&to).is_ok()
+    };
+    if taken {
+        return Err(NameError::Exists(to));
+    }
+    Ok(to)
+}
+
+/// Whether `from` and `to` are in the same folder and their names differ only in case.
+pub fn is_case_only_change(from: &Path, to: &Path) -> bool {
+    let (Some(from_name), Some(to_name)) = (from.file_name(), to.file_name()) else {
+        return false;
+    };
+    from.parent() == to.parent()
+        && from_name != to_name
+        // Names that aren't UTF-8 have no case to compare
+        && matches!((from_name.to_str(), to_name.to_str()), (Some(a), Some(b)) if a.to_lowercase() == b.to_lowercase())
+}
+
+/// Whether the folder holding `path` has an entry spelled exactly like it.
+/// Unlike [`FileSystem::metadata`], this tells `a.txt` and `A.txt` apart on case-insensitive drives.
+pub fn exists_exactly(fs: &dyn FileSystem, path: &Path) -> bool {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    fs.read_dir(dir)
+        .map(|entries| entries.iter().any(|entry| entry.path.file_name() == Some(name)))
+        .unwrap_or(false)
+}
+
+/// A free path next to `path` for keeping both items: `notes (2).txt`, `notes (3).txt`, ...
+/// Folder names are numbered as a whole, without looking for an extension.
+pub fn keep_both_name(fs: &dyn FileSystem, path: &Path) -> PathBuf {
+    let is_dir = fs.metadata(path).is_ok_and(|entry| entry.is_dir);
+    // A leading dot (`.bashrc`) starts the name rather than an extension, which `file_stem` knows
+    let (stem, extension) = match is_dir {
+        false => (path.file_stem().unwrap_or_default(), path.extension()),
+        true => (path.file_name().unwrap_or_default(), None),
+    };
+    (2..)
+        .map(|n| {
+            let mut name = stem.to_os_string();
+            name.push(format!(" ({})", n));
+            if let Some(extension) = extension {
+                name.push(".");
+                name.push(extension);
+            }
+            path.with_file_name(name)
+        })
+        .find(|candidate| fs.metadata(candidate).is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::MemoryFs;
+    use crate::ops;
+
+    #[test]
+    fn validate_name_accepts_ordinary_names() {
+        for name in ["notes.txt", ".bashrc", "a b", "résumé", "console.log", "COM10"] {
+            assert_eq!(validate_name(name), Ok(()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn validate_name_refuses_names_that_dont_work_everywhere() {
+        assert_eq!(validate_name(""), Err(NameError::Empty));
+        assert_eq!(validate_name("   "), Err(NameError::Empty));
+        assert_eq!(validate_name(".."), Err(NameError::DotName));
+        assert_eq!(validate_name("a/b"), Err(NameError::IllegalChar('/')));
+        assert_eq!(validate_name("what?"), Err(NameError::IllegalChar('?')));
+        assert_eq!(validate_name("back\\slash"), Err(NameError::IllegalChar('\\')));
+        assert_eq!(validate_name("bell\u{7}"), Err(NameError::IllegalChar('\u{7}')));
+        assert_eq!(validate_name("name."), Err(NameError::TrailingDotOrSpace));
+        assert_eq!(validate_name("name "), Err(NameError::TrailingDotOrSpace));
+        assert_eq!(validate_name(&"a".repeat(256)), Err(NameError::TooLong));
+        assert_eq!(validate_name("nul.txt"), Err(NameError::Reserved(String::from("NUL"))));
+        assert_eq!(validate_name("Com1"), Err(NameError::Reserved(String::from("COM1"))));
+    }
+
+    #[test]
+    fn check_new_name_refuses_existing_items() {
+        let fs = MemoryFs::new();
+        ops::create_file(&fs, Path::new(""), "a.txt", "").unwrap();
+        assert_eq!(check_new_name(&fs, Path::new(""), "a.txt"), Err(NameError::Exists(PathBuf::from("a.txt"))));
+        assert_eq!(check_new_name(&fs, Path::new(""), "b.txt"), Ok(PathBuf::from("b.txt")));
+    }
+
+    #[test]
+    fn check_rename_allows_the_same_name_and_case_changes() {
+        let fs = MemoryFs::new();
+        ops::create_file(&fs, Path::new(""), "a.txt", "").unwrap();
+        ops::create_file(&fs, Path::new(""), "b.txt", "").unwrap();
+        let from = Path::new("a.txt");
+        assert_eq!(check_rename(&fs, from, "a.txt"), Ok(PathBuf::from("a.txt")));
+        assert_eq!(check_rename(&fs, from, "A.txt"), Ok(PathBuf::from("A.txt")));
+        assert_eq!(check_rename(&fs, from, "b.txt"), Err(NameError::Exists(PathBuf::from("b.txt"))));
+        assert_eq!(check_rename(&fs, from, "c?"), Err(NameError::IllegalChar('?')));
+    }
+
+    #[test]
+    fn keep_both_name_numbers_the_stem() {
+        let fs = MemoryFs::new();
+        ops::create_file(&fs, Path::new(""), "notes.txt", "").unwrap();
+        assert_eq!(keep_both_name(&fs, Path::new("notes.txt")), Path::new("notes (2).txt"));
+        ops::create_file(&fs, Path::new(""), "notes (2).txt", "").unwrap();
+        assert_eq!(keep_both_name(&fs, Path::new("notes.txt")), Path::new("notes (3).txt"));
+        ops::create_file(&fs, Path::new(""), ".bashrc", "").unwrap();
+        assert_eq!(keep_both_name(&fs, Path::new(".bashrc")), Path::new(".bashrc (2)"));
+    }
+
+    #[test]
+    fn keep_both_name_numbers_folders_as_a_whole() {
+        let fs = MemoryFs::new();
+        ops::create_folder(&fs, Path::new(""), "v1.2").unwrap();
+        assert_eq!(keep_both_name(&fs, Path::new("v1.2")), Path::new("v1.2 (2)"));
+    }
+}
@@ -0,0 +1,362 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::{FsError, IoResultExt, Result};
+use crate::fs::{Entry, FileSystem};
+use crate::names;
+use crate::paths;
+
+/// Creates a new file called `file_name` inside `dir` and writes `content` to it.
+/// Returns the full path of the new file.
+pub fn create_file(
+    fs: &dyn FileSystem,
+    dir: &Path,
+    file_name: &str,
+    content: &str,
+) -> Result<PathBuf> {
+    let full_path = dir.join(file_name); // Full file path
+    fs.create_file(&full_path).at(&full_path)?;
+    save_content(fs, content, &full_path)?; // Save the provided content to the file
+    Ok(full_path)
+}
+
+/// Creates a new folder called `folder_name` inside `dir`.
+/// Returns the full path of the new folder.
+pub fn create_folder(fs: &dyn FileSystem, dir: &Path, folder_name: &str) -> Result<PathBuf> {
+    let folder_path = dir.join(folder_name); // Full folder path
+    fs.create_dir(&folder_path).at(&folder_path)?;
+    Ok(folder_path)
+}
+
+/// Saves `content` to `file_path`, replacing whatever was there.
+pub fn save_content(fs: &dyn FileSystem, content: &str, file_path: &Path) -> Result<()> {
+    fs.write(file_path, content.as_bytes()).at(file_path)
+}
+
+/// Reads a file as text for the editor.
+/// Fails with [`FsError::InvalidUtf8`] if the file is not valid UTF-8.
+pub fn open_file_content(fs: &dyn FileSystem, file_path: &Path) -> Result<String> {
+    let bytes = fs.read(file_path).at(file_path)?;
+    String::from_utf8(bytes).map_err(|_| FsError::InvalidUtf8(file_path.to_path_buf()))
+}
+
+/// Renames or moves `from` to `to`, refusing to replace anything already at `to`.
+/// Case-only renames go through a temporary name, since case-insensitive drives
+/// would otherwise see `to` as `from` and do nothing.
+pub fn rename(fs: &dyn FileSystem, from: &Path, to: &Path) -> Result<()> {
+    if !names::is_case_only_change(from, to) {
+        if fs.metadata(to).is_ok() {
+            return Err(FsError::AlreadyExists(to.to_path_buf()));
+        }
+        return fs.rename(from, to).at(from);
+    }
+
+    if names::exists_exactly(fs, to) {
+        return Err(FsError::AlreadyExists(to.to_path_buf()));
+    }
+    let mut temporary_name = OsString::from(".");
+    temporary_name.push(from.file_name().unwrap_or_default());
+    temporary_name.push(format!(".renaming-{}", std::process::id()));
+    let temporary = from.with_file_name(temporary_name);
+    fs.rename(from, &temporary).at(from)?;
+    fs.rename(&temporary, to).at(to).inspect_err(|_| {
+        let _ = fs.rename(&temporary, from); // Put it back under its old name
+    })
+}
+
+/// Moves `from` to `to` like [`rename`]. Between drives, where renaming fails
+/// with EXDEV, the item is copied over and the original removed.
+pub fn move_item(fs: &dyn FileSystem, from: &Path, to: &Path) -> Result<()> {
+    move_item_with_progress(fs, from, to, &Progress::default())
+}
+
+/// Like [`move_item`], counting the items into `progress` when they have to be copied.
+pub fn move_item_with_progress(fs: &dyn FileSystem, from: &Path, to: &Path, progress: &Progress) -> Result<()> {
+    check_not_into_itself(from, to)?;
+    match rename(fs, from, to) {
+        Err(FsError::CrossesDevices(_)) => {
+            copy_with_progress(fs, from, to, progress)?;
+            remove(fs, from)
+        }
+        result => result,
+    }
+}
+
+/// How far along a copy or an archive is, in files and folders. Updated as the copy goes,
+/// so it can be read from another thread to show progress.
+#[derive(Debug, Default)]
+pub struct Progress {
+    total: AtomicU64,
+    done: AtomicU64,
+}
+
+impl Progress {
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn add_total(&self, items: u64) {
+        self.total.fetch_add(items, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_done(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Copies a file or a whole folder to `to`, keeping permissions and timestamps.
+/// Refuses to replace anything at `to`; a copy that fails halfway is removed again.
+pub fn copy(fs: &dyn FileSystem, from: &Path, to: &Path) -> Result<()> {
+    copy_with_progress(fs, from, to, &Progress::default())
+}
+
+/// Like [`copy`], counting the items to copy into `progress` first and then each one as it's copied.
+pub fn copy_with_progress(fs: &dyn FileSystem, from: &Path, to: &Path, progress: &Progress) -> Result<()> {
+    check_not_into_itself(from, to)?;
+    if fs.metadata(to).is_ok() {
+        return Err(FsError::AlreadyExists(to.to_path_buf()));
+    }
+    let entry = fs.metadata(from).at(from)?;
+    progress.total.fetch_add(count_items(fs, &entry), Ordering::Relaxed);
+    let result = copy_recursive(fs, &entry, to, progress);
+    if result.is_err() {
+        let _ = remove(fs, to);
+    }
+    result
+}
+
+// Links are copied as links, never followed, so a link to `..` can't make the copy endless
+fn copy_recursive(fs: &dyn FileSystem, entry: &Entry, to: &Path, progress: &Progress) -> Result<()> {
+    let from = entry.path.as_path();
+    if entry.is_symlink {
+        let target = fs.read_link(from).at(from)?;
+        fs.symlink(&target, to).at(to)?;
+        progress.done.fetch_add(1, Ordering::Relaxed);
+        return Ok(());
+    }
+    if !entry.is_dir {
+        fs.copy_file(from, to).at(to)?;
+        progress.done.fetch_add(1, Ordering::Relaxed);
+        return Ok(());
+    }
+    fs.create_dir(to).at(to)?;
+    for child in fs.read_dir(from).at(from)? {
+        let name = child.path.file_name().unwrap_or_default();
+        copy_recursive(fs, &child, &to.join(name), progress)?;
+    }
+    fs.copy_attributes(from, to).at(to)?; // Last, copying the content changed the folder's timestamps
+    progress.done.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+}
+
+// The item itself plus everything below it; links and unreadable folders count as one
+pub(crate) fn count_items(fs: &dyn FileSystem, entry: &Entry) -> u64 {
+    if !entry.is_dir || entry.is_symlink {
+        return 1;
+    }
+    let Ok(children) = fs.read_dir(&entry.path) else {
+        return 1;
+    };
+    1 + children.iter().map(|child| count_items(fs, child)).sum::<u64>()
+}
+
+/// Fails with [`FsError::IntoItself`] if `to` is `from` or somewhere inside it.
+pub fn check_not_into_itself(from: &Path, to: &Path) -> Result<()> {
+    let (Ok(from_absolute), Ok(to_absolute)) = (paths::normalize(from), paths::normalize(to)) else {
+        return Ok(()); // Nothing to compare against, the operation itself will fail
+    };
+    if to_absolute.starts_with(&from_absolute) {
+        return Err(FsError::IntoItself(from.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Deletes a file, or a folder and everything inside it. A link is removed, not what it points to.
+pub fn remove(fs: &dyn FileSystem, path: &Path) -> Result<()> {
+    let entry = fs.metadata(path).at(path)?;
+    if entry.is_dir && !entry.is_symlink {
+        delete_folder(fs, path)
+    } else {
+        delete_file(fs, path)
+    }
+}
+
+/// Deletes a single file.
+pub fn delete_file(fs: &dyn FileSystem, file_path: &Path) -> Result<()> {
+    fs.remove_file(file_path).at(file_path)
+}
+
+/// Deletes a folder and everything inside it.
+pub fn delete_folder(fs: &dyn FileSystem, folder_path: &Path) -> Result<()> {
+    fs.remove_dir_all(folder_path).at(folder_path)
+}
+
+/// Lists a directory the way the grid shows it: folders first, then files.
+pub fn list_directory(fs: &dyn FileSystem, dir: &Path) -> Result<Vec<Entry>> {
+    let (folders, files): (Vec<Entry>, Vec<Entry>) =
+        fs.read_dir(dir).at(dir)?.into_iter().partition(|entry| entry.is_dir);
+    Ok([folders, files].concat()) // Concatenate the folders and files into one list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{LocalFs, MemoryFs};
+    use crate::testing::TempDir;
+
+    // A `root` folder holding `a/x.txt` and `b.txt`
+    fn sample() -> MemoryFs {
+        let fs = MemoryFs::new();
+        create_folder(&fs, Path::new(""), "root").unwrap();
+        create_folder(&fs, Path::new("root"), "a").unwrap();
+        create_file(&fs, Path::new("root/a"), "x.txt", "x").unwrap();
+        create_file(&fs, Path::new("root"), "b.txt", "b").unwrap();
+        fs
+    }
+
+    #[test]
+    fn create_file_writes_its_content() {
+        let fs = sample();
+        let path = create_file(&fs, Path::new("root"), "notes.txt", "hello").unwrap();
+        assert_eq!(path, Path::new("root/notes.txt"));
+        assert_eq!(open_file_content(&fs, &path).unwrap(), "hello");
+    }
+
+    #[test]
+    fn create_folder_refuses_an_existing_name() {
+        let fs = sample();
+        assert!(matches!(create_folder(&fs, Path::new("root"), "a"), Err(FsError::AlreadyExists(_))));
+    }
+
+    #[test]
+    fn open_file_content_refuses_binary_files() {
+        let fs = sample();
+        fs.write(Path::new("root/bin"), &[0xff, 0xfe]).unwrap();
+        assert!(matches!(open_file_content(&fs, Path::new("root/bin")), Err(FsError::InvalidUtf8(_))));
+    }
+
+    #[test]
+    fn rename_refuses_to_replace_anything() {
+        let fs = sample();
+        assert!(matches!(
+            rename(&fs, Path::new("root/b.txt"), Path::new("root/a")),
+            Err(FsError::AlreadyExists(_))
+        ));
+        rename(&fs, Path::new("root/b.txt"), Path::new("root/c.txt")).unwrap();
+        assert_eq!(open_file_content(&fs, Path::new("root/c.txt")).unwrap(), "b");
+        assert!(fs.metadata(Path::new("root/b.txt")).is_err());
+    }
+
+    #[test]
+    fn rename_changes_only_the_case() {
+        let fs = sample();
+        rename(&fs, Path::new("root/b.txt"), Path::new("root/B.txt")).unwrap();
+        assert!(names::exists_exactly(&fs, Path::new("root/B.txt")));
+        assert!(!names::exists_exactly(&fs, Path::new("root/b.txt")));
+    }
+
+    #[test]
+    fn copy_copies_a_folder_with_everything_inside() {
+        let fs = sample();
+        let progress = Progress::default();
+        copy_with_progress(&fs, Path::new("root/a"), Path::new("root/copy"), &progress).unwrap();
+        assert_eq!(open_file_content(&fs, Path::new("root/copy/x.txt")).unwrap(), "x");
+        assert_eq!(open_file_content(&fs, Path::new("root/a/x.txt")).unwrap(), "x");
+        assert_eq!((progress.total(), progress.done()), (2, 2));
+    }
+
+    #[test]
+    fn copy_refuses_to_replace_anything() {
+        let fs = sample();
+        assert!(matches!(copy(&fs, Path::new("root/b.txt"), Path::new("root/a")), Err(FsError::AlreadyExists(_))));
+    }
+
+    #[test]
+    fn move_item_moves_a_folder() {
+        let fs = sample();
+        move_item(&fs, Path::new("root/a"), Path::new("root/moved")).unwrap();
+        assert_eq!(open_file_content(&fs, Path::new("root/moved/x.txt")).unwrap(), "x");
+        assert!(fs.metadata(Path::new("root/a")).is_err());
+    }
+
+    #[test]
+    fn a_folder_cant_go_inside_itself() {
+        let fs = sample();
+        for to in ["root/a", "root/a/inner"] {
+            assert!(matches!(move_item(&fs, Path::new("root/a"), Path::new(to)), Err(FsError::IntoItself(_))));
+            assert!(matches!(copy(&fs, Path::new("root/a"), Path::new(to)), Err(FsError::IntoItself(_))));
+        }
+        // `root/ab` only starts with the same letters
+        assert!(check_not_into_itself(Path::new("root/a"), Path::new("root/ab")).is_ok());
+    }
+
+    #[test]
+    fn remove_deletes_files_and_folders() {
+        let fs = sample();
+        remove(&fs, Path::new("root/a")).unwrap();
+        remove(&fs, Path::new("root/b.txt")).unwrap();
+        assert!(fs.read_dir(Path::new("root")).unwrap().is_empty());
+        assert!(matches!(remove(&fs, Path::new("root/a")), Err(FsError::NotFound(_))));
+    }
+
+    #[test]
+    fn list_directory_puts_folders_first() {
+        let fs = sample();
+        create_file(&fs, Path::new("root"), "0.txt", "").unwrap();
+        let listed: Vec<PathBuf> = list_directory(&fs, Path::new("root")).unwrap().into_iter().map(|entry| entry.path).collect();
+        assert_eq!(listed[0], Path::new("root/a"));
+        assert_eq!(listed.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_keeps_a_link_to_a_parent_as_a_link() {
+        let dir = TempDir::new();
+        let from = dir.path().join("from");
+        std::fs::create_dir_all(from.join("sub")).unwrap();
+        std::os::unix::fs::symlink("..", from.join("sub/up")).unwrap();
+
+        let to = dir.path().join("to");
+        let progress = Progress::default();
+        copy_with_progress(&LocalFs, &from, &to, &progress).unwrap();
+
+        let up = to.join("sub/up");
+        assert!(std::fs::symlink_metadata(&up).unwrap().is_symlink());
+        assert_eq!(std::fs::read_link(&up).unwrap(), Path::new(".."));
+        assert_eq!((progress.total(), progress.done()), (3, 3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_keeps_a_link_to_a_file_as_a_link() {
+        let dir = TempDir::new();
+        std::fs::write(dir.path().join("target.txt"), "hello").unwrap();
+        std::os::unix::fs::symlink("target.txt", dir.path().join("link.txt")).unwrap();
+
+        let to = dir.path().join("copy.txt");
+        copy(&LocalFs, &dir.path().join("link.txt"), &to).unwrap();
+
+        assert!(std::fs::symlink_metadata(&to).unwrap().is_symlink());
+        assert_eq!(std::fs::read_link(&to).unwrap(), Path::new("target.txt"));
+        assert_eq!(std::fs::read_to_string(&to).unwrap(), "hello");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn remove_deletes_a_link_to_a_folder_but_not_the_folder() {
+        let dir = TempDir::new();
+        std::fs::create_dir(dir.path().join("folder")).unwrap();
+        std::fs::write(dir.path().join("folder/keep.txt"), "").unwrap();
+        std::os::unix::fs::symlink("folder", dir.path().join("link")).unwrap();
+
+        remove(&LocalFs, &dir.path().join("link")).unwrap();
+
+        assert!(std::fs::symlink_metadata(dir.path().join("link")).is_err());
+        assert!(dir.path().join("folder/keep.txt").exists());
+    }
+}
@@ -0,0 +1,48 @@
+//! Path handling that keeps the OS's own bytes, so names that aren't valid UTF-8 survive.
+
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// `path` made absolute against the working directory, with `.` and `..` resolved.
+///
+/// Works on the text of the path and doesn't follow symlinks, so `link/..` is the folder
+/// holding `link` just like in a shell's `cd`. `..` at the root stays at the root.
+pub fn normalize(path: &Path) -> io::Result<PathBuf> {
+    let absolute = std::path::absolute(path)?;
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn normalize_resolves_dots() {
+        assert_eq!(normalize(Path::new("/a/./b/../c")).unwrap(), Path::new("/a/c"));
+        assert_eq!(normalize(Path::new("/a/b/")).unwrap(), Path::new("/a/b"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn normalize_stays_at_the_root() {
+        assert_eq!(normalize(Path::new("/../..")).unwrap(), Path::new("/"));
+    }
+
+    #[test]
+    fn normalize_makes_relative_paths_absolute() {
+        let current = std::env::current_dir().unwrap();
+        assert_eq!(normalize(Path::new("x/../y")).unwrap(), current.join("y"));
+        assert_eq!(normalize(Path::new(".")).unwrap(), current);
+    }
+}
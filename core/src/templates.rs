@@ -0,0 +1,63 @@
+//! Starting content for new files.
+//!
+//! A templates folder holds one file per template: `Markdown.md` becomes the
+//! "Markdown" template, creating `.md` files that start with its content.
+//! `{{name}}` in the content is replaced by the new file's name without extension.
+
+use std::path::Path;
+
+use crate::error::{IoResultExt, Result};
+use crate::fs::FileSystem;
+
+/// One template the user can pick when creating a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    /// Extension suggested for files made from it, without the dot. May be empty.
+    pub extension: String,
+    pub content: String,
+}
+
+impl Template {
+    /// The blank template, always offered first.
+    pub fn empty() -> Self {
+        Self {
+            name: "Empty".to_string(),
+            extension: "txt".to_string(),
+            content: String::new(),
+        }
+    }
+
+    /// The content for a file called `file_name`, with `{{name}}` filled in.
+    pub fn render(&self, file_name: &str) -> String {
+        let stem = Path::new(file_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        self.content.replace("{{name}}", &stem)
+    }
+}
+
+/// Loads every template in `dir`, sorted by name, after the [`Template::empty`] one.
+/// Files that aren't valid UTF-8 are skipped.
+pub fn load_templates(fs: &dyn FileSystem, dir: &Path) -> Result<Vec<Template>> {
+    let mut templates: Vec<Template> = fs
+        .read_dir(dir)
+        .at(dir)?
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .filter_map(|entry| {
+            let content = String::from_utf8(fs.read(&entry.path).ok()?).ok()?;
+            let name = entry.path.file_stem()?.to_string_lossy().into_owned();
+            let extension = entry
+                .path
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Some(Template { name, extension, content })
+        })
+        .collect();
+    templates.sort_by_key(|template| template.name.to_lowercase());
+    templates.insert(0, Template::empty());
+    Ok(templates)
+}
@@ -0,0 +1,236 @@
+//! Where thumbnails live according to the freedesktop.org Thumbnail Managing
+//! Standard, so thumbnails we make are reused by other programs and vice versa.
+//!
+//! Each thumbnail is a PNG in `~/.cache/thumbnails/<size>/`, named after the MD5 of
+//! the original's `file://` URI. The PNG records that URI and the original's
+//! modification time in its `Thumb::URI` and `Thumb::MTime` text chunks; a
+//! thumbnail whose time doesn't match the file any more is out of date.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Text chunk holding the original's URI.
+pub const URI_KEY: &str = "Thumb::URI";
+/// Text chunk holding the original's modification time, in whole seconds since the epoch.
+pub const MTIME_KEY: &str = "Thumb::MTime";
+
+/// Extensions of the images we know how to thumbnail, in lowercase.
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// The two sizes the standard defines, each with its own subdirectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThumbnailSize {
+    /// At most 128×128 pixels.
+    Normal,
+    /// At most 256×256 pixels.
+    Large,
+}
+
+impl ThumbnailSize {
+    /// The smallest size that still looks sharp when drawn `edge` pixels wide.
+    pub fn for_edge(edge: f32) -> Self {
+        if edge <= 128.0 {
+            ThumbnailSize::Normal
+        } else {
+            ThumbnailSize::Large
+        }
+    }
+
+    /// The longest side of a thumbnail of this size.
+    pub fn pixels(self) -> u32 {
+        match self {
+            ThumbnailSize::Normal => 128,
+            ThumbnailSize::Large => 256,
+        }
+    }
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            ThumbnailSize::Normal => "normal",
+            ThumbnailSize::Large => "large",
+        }
+    }
+}
+
+/// The thumbnail cache: `$XDG_CACHE_HOME/thumbnails`, falling back to `~/.cache/thumbnails`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThumbnailCache {
+    root: PathBuf,
+}
+
+impl ThumbnailCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The user's cache. `None` if neither `XDG_CACHE_HOME` nor `HOME` is set.
+    pub fn home() -> Option<Self> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(Self::new(cache_home.join("thumbnails")))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the thumbnail of the file at `uri` goes, e.g. `normal/<md5>.png`.
+    pub fn path(&self, size: ThumbnailSize, uri: &str) -> PathBuf {
+        let name: String = md5(uri.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+        self.root.join(size.dir_name()).join(name + ".png")
+    }
+
+    /// Whether `path` is inside the cache. Thumbnails of thumbnails are never made.
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+}
+
+/// Whether `path` looks like an image we can thumbnail, going by its extension.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// The `file://` URI of an absolute path, escaped the way GLib does it, since
+/// the cache name has to match byte for byte what other programs compute.
+pub fn file_uri(path: &Path) -> String {
+    const UNESCAPED: &[u8] = b"!$&'()*+,-./:=@_~";
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        if byte.is_ascii_alphanumeric() || UNESCAPED.contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// `Thumb::MTime` for a modification time: whole seconds since the epoch.
+pub fn mtime_text(modified: SystemTime) -> Option<String> {
+    modified.duration_since(UNIX_EPOCH).ok().map(|since| since.as_secs().to_string())
+}
+
+/// MD5 as specified in RFC 1321. Used only to name cache files, not for security.
+fn md5(message: &[u8]) -> [u8; 16] {
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    // The integer parts of 2^32 × |sin(i + 1)|
+    let constants: Vec<u32> = (0..64).map(|i| ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32).collect();
+
+    let mut data = message.to_vec();
+    data.push(0x80);
+    while data.len() % 64 != 56 {
+        data.push(0);
+    }
+    data.extend_from_slice(&((message.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in data.chunks_exact(64) {
+        let words: Vec<u32> = block.chunks_exact(4).map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]])).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(constants[i]).wrapping_add(words[g]).rotate_left(SHIFTS[i]);
+            (a, d, c) = (d, c, b);
+            b = b.wrapping_add(rotated);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0; 16];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(message: &str) -> String {
+        md5(message.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn md5_matches_the_rfc_1321_test_suite() {
+        assert_eq!(hex(""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hex("a"), "0cc175b9c0f1b6a831c399e269772661");
+        assert_eq!(hex("abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(hex("message digest"), "f96b697d7cb7938d525a2f31aaf161d0");
+        assert_eq!(hex("abcdefghijklmnopqrstuvwxyz"), "c3fcd3d76192e4007dfb496cca67e13b");
+        assert_eq!(
+            hex("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789"),
+            "d174ab98d277d9f5a5611c2c9f419d9f"
+        );
+        assert_eq!(
+            hex("12345678901234567890123456789012345678901234567890123456789012345678901234567890"),
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+
+    #[test]
+    fn md5_pads_messages_around_a_block_boundary() {
+        // 55 bytes still fit the length in the same block, 56 don't
+        assert_eq!(hex(&"a".repeat(55)), "ef1772b6dff9a122358552954ad0df65");
+        assert_eq!(hex(&"a".repeat(56)), "3b0c8ac703f828b04c6c197006d17218");
+        assert_eq!(hex(&"a".repeat(64)), "014842d480b571495a4a0363793f7367");
+    }
+
+    #[test]
+    fn cache_names_match_the_spec_example() {
+        let cache = ThumbnailCache::new("/cache");
+        assert_eq!(
+            cache.path(ThumbnailSize::Normal, "file:///home/jens/photos/me.png"),
+            Path::new("/cache/normal/c6ee772d9e49320e97ec29a7eb5b1697.png")
+        );
+        assert!(cache.path(ThumbnailSize::Large, "file:///a").starts_with("/cache/large"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_uri_escapes_like_glib() {
+        assert_eq!(file_uri(Path::new("/home/me/photo.png")), "file:///home/me/photo.png");
+        assert_eq!(file_uri(Path::new("/a b/c#1.png")), "file:///a%20b/c%231.png");
+        assert_eq!(file_uri(Path::new("/100%.png")), "file:///100%25.png");
+        assert_eq!(file_uri(Path::new("/naïve.png")), "file:///na%C3%AFve.png");
+        assert_eq!(file_uri(Path::new("/it's (1)+[2]~.png")), "file:///it's%20(1)+%5B2%5D~.png");
+        assert_eq!(file_uri(Path::new("/q?x=y;z")), "file:///q%3Fx=y%3Bz");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_uri_keeps_names_that_arent_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/bad\xff.png"));
+        assert_eq!(file_uri(path), "file:///bad%FF.png");
+    }
+
+    #[test]
+    fn images_are_recognised_by_extension() {
+        assert!(is_image(Path::new("a.PNG")));
+        assert!(is_image(Path::new("dir/b.jpeg")));
+        assert!(!is_image(Path::new("c.svg")));
+        assert!(!is_image(Path::new("png")));
+    }
+
+    #[test]
+    fn mtime_is_whole_seconds() {
+        let modified = UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_999);
+        assert_eq!(mtime_text(modified).as_deref(), Some("1700000000"));
+    }
+}
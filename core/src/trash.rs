@@ -0,0 +1,409 @@
+//! A trash can following the freedesktop.org Trash specification, so items we
+//! delete show up in (and can be restored from) the desktop's own trash too.
+//!
+//! Each trashed item is moved into `files/` and gets a matching
+//! `info/<name>.trashinfo` recording where it came from and when.
+
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime, Timelike};
+
+use crate::error::{FsError, IoResultExt, Result};
+use crate::fs::FileSystem;
+use crate::ops;
+
+const INFO_EXTENSION: &str = ".trashinfo";
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// One item sitting in the trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashItem {
+    /// Where the item lives now, inside the trash's `files/` directory.
+    pub trashed_path: PathBuf,
+    /// Where it was deleted from, and where [`Trash::restore`] puts it back.
+    pub original_path: PathBuf,
+    /// When it was deleted, in local time. `None` if the info file had no valid date.
+    pub deleted_at: Option<NaiveDateTime>,
+    pub is_dir: bool,
+}
+
+impl TrashItem {
+    /// The name the item had before it was deleted, for display.
+    pub fn name(&self) -> String {
+        self.original_path
+            .file_name()
+            .unwrap_or(self.trashed_path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// A trash directory (e.g. `~/.local/share/Trash`) with its `files/` and `info/` subdirectories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trash {
+    root: PathBuf,
+}
+
+impl Trash {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The user's home trash: `$XDG_DATA_HOME/Trash`, falling back to `~/.local/share/Trash`.
+    pub fn home() -> Option<Self> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+        Some(Self::new(data_home.join("Trash")))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn files_dir(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn info_dir(&self) -> PathBuf {
+        self.root.join("info")
+    }
+
+    fn info_path(&self, trashed_name: &OsStr) -> PathBuf {
+        let mut file_name = trashed_name.to_os_string();
+        file_name.push(INFO_EXTENSION);
+        self.info_dir().join(file_name)
+    }
+
+    /// Moves `path` into the trash. Relative paths are made absolute against the
+    /// current directory, since the info file must record an absolute location.
+    ///
+    /// Items on another file system than the trash are copied into it and then
+    /// removed, like [`ops::move_item`] does, which can take a while for big folders.
+    /// If the copy fails partway it's removed again and the original is untouched, but
+    /// when that clean-up or removing the original fails, a partial copy is left in
+    /// the trash's `files/` directory without an info file, and part of the original
+    /// may already be gone.
+    pub fn put(&self, fs: &dyn FileSystem, path: &Path) -> Result<TrashItem> {
+        let original_path = std::path::absolute(path).at(path)?;
+        let entry = fs.metadata(path).at(path)?;
+        let Some(file_name) = original_path.file_name() else {
+            return Err(FsError::Io {
+                path: path.to_path_buf(),
+                source: io::Error::new(io::ErrorKind::InvalidInput, "can't be moved to the trash"),
+            });
+        };
+
+        fs.create_dir_all(&self.files_dir()).at(&self.files_dir())?;
+        fs.create_dir_all(&self.info_dir()).at(&self.info_dir())?;
+        let trashed_name = self.free_name(fs, file_name);
+
+        // The spec wants the info file written first, so a crash never leaves an item without one
+        let now = Local::now().naive_local();
+        let deleted_at = now.with_nanosecond(0).unwrap_or(now); // The info file only keeps whole seconds
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(&original_path),
+            deleted_at.format(DATE_FORMAT)
+        );
+        let info_path = self.info_path(&trashed_name);
+        fs.write(&info_path, info.as_bytes()).at(&info_path)?;
+
+        let trashed_path = self.files_dir().join(&trashed_name);
+        // Items on another drive are copied into the trash and removed
+        if let Err(e) = ops::move_item(fs, path, &trashed_path) {
+            let _ = fs.remove_file(&info_path); // Don't leave a dangling entry behind
+            return Err(e);
+        }
+
+        Ok(TrashItem {
+            trashed_path,
+            original_path,
+            deleted_at: Some(deleted_at),
+            is_dir: entry.is_dir && !entry.is_symlink, // A link to a folder is removed like a file
+        })
+    }
+
+    /// Everything in the trash, most recently deleted first.
+    /// Info files whose item is missing (or the other way round) are skipped.
+    pub fn list(&self, fs: &dyn FileSystem) -> Result<Vec<TrashItem>> {
+        let infos = match fs.read_dir(&self.info_dir()) {
+            Ok(infos) => infos,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()), // Nothing was ever trashed
+            Err(e) => return Err(FsError::from_io(&self.info_dir(), e)),
+        };
+
+        let mut items: Vec<TrashItem> = infos
+            .iter()
+            .filter_map(|info| {
+                // Byte-wise, so names that aren't valid UTF-8 are listed too
+                let info_name = path_bytes(Path::new(info.path.file_name()?));
+                let trashed_name = info_name.strip_suffix(INFO_EXTENSION.as_bytes())?;
+                let trashed_path = self.files_dir().join(path_from_bytes(trashed_name.to_vec()));
+                let entry = fs.metadata(&trashed_path).ok()?;
+                let contents = fs.read(&info.path).ok()?;
+                let (original_path, deleted_at) = parse_info(&String::from_utf8_lossy(&contents))?;
+                Some(TrashItem {
+                    trashed_path,
+                    original_path,
+                    deleted_at,
+                    is_dir: entry.is_dir && !entry.is_symlink,
+                })
+            })
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+        Ok(items)
+    }
+
+    /// Moves an item back to where it was deleted from, recreating missing parent folders.
+    /// Refuses to overwrite anything that has since appeared at that location.
+    pub fn restore(&self, fs: &dyn FileSystem, item: &TrashItem) -> Result<PathBuf> {
+        if fs.metadata(&item.original_path).is_ok() {
+            return Err(FsError::AlreadyExists(item.original_path.clone()));
+        }
+        if let Some(parent) = item.original_path.parent() {
+            fs.create_dir_all(parent).at(parent)?;
+        }
+        ops::move_item(fs, &item.trashed_path, &item.original_path)?;
+        self.forget(fs, item);
+        Ok(item.original_path.clone())
+    }
+
+    /// Deletes an item from the trash for good.
+    pub fn purge(&self, fs: &dyn FileSystem, item: &TrashItem) -> Result<()> {
+        if item.is_dir {
+            fs.remove_dir_all(&item.trashed_path).at(&item.trashed_path)?;
+        } else {
+            fs.remove_file(&item.trashed_path).at(&item.trashed_path)?;
+        }
+        self.forget(fs, item);
+        Ok(())
+    }
+
+    /// Purges every item, stopping at the first failure.
+    pub fn empty(&self, fs: &dyn FileSystem) -> Result<()> {
+        for item in self.list(fs)? {
+            self.purge(fs, &item)?;
+        }
+        Ok(())
+    }
+
+    // Drops the info file once the item itself has left the trash
+    fn forget(&self, fs: &dyn FileSystem, item: &TrashItem) {
+        if let Some(trashed_name) = item.trashed_path.file_name() {
+            let _ = fs.remove_file(&self.info_path(trashed_name));
+        }
+    }
+
+    // `name`, or `stem.2.ext`, `stem.3.ext`, ... if that is already taken in the trash
+    fn free_name(&self, fs: &dyn FileSystem, name: &OsStr) -> OsString {
+        let is_free = |candidate: &OsStr| {
+            fs.metadata(&self.files_dir().join(candidate)).is_err()
+                && fs.metadata(&self.info_path(candidate)).is_err()
+        };
+        if is_free(name) {
+            return name.to_os_string();
+        }
+
+        let path = Path::new(name);
+        let stem = path.file_stem().unwrap_or(name);
+        let mut counter = 2;
+        loop {
+            let mut candidate = stem.to_os_string();
+            candidate.push(format!(".{}", counter));
+            if let Some(extension) = path.extension() {
+                candidate.push(".");
+                candidate.push(extension);
+            }
+            if is_free(&candidate) {
+                return candidate;
+            }
+            counter += 1;
+        }
+    }
+}
+
+fn parse_info(contents: &str) -> Option<(PathBuf, Option<NaiveDateTime>)> {
+    let mut lines = contents.lines();
+    if lines.next()?.trim() != "[Trash Info]" {
+        return None;
+    }
+    let mut original_path = None;
+    let mut deleted_at = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Path=") {
+            original_path = Some(decode_path(value.trim()));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deleted_at = NaiveDateTime::parse_from_str(value.trim(), DATE_FORMAT).ok();
+        }
+    }
+    Some((original_path?, deleted_at))
+}
+
+// The spec stores paths URL-escaped, byte by byte, so non-UTF-8 names survive
+fn encode_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for &byte in path_bytes(path).iter() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn decode_path(encoded: &str) -> PathBuf {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    path_from_bytes(decoded)
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::LocalFs;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn put_and_restore_a_file() {
+        let dir = TempDir::new();
+        let trash = Trash::new(dir.path().join("trash"));
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+
+        let item = trash.put(&LocalFs, &path).unwrap();
+        assert!(!path.exists());
+        assert_eq!(item.original_path, path);
+        assert_eq!(trash.list(&LocalFs).unwrap(), vec![item.clone()]);
+
+        assert_eq!(trash.restore(&LocalFs, &item).unwrap(), path);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+        assert!(trash.list(&LocalFs).unwrap().is_empty());
+    }
+
+    #[test]
+    fn put_and_restore_a_folder_with_an_unusual_name() {
+        let dir = TempDir::new();
+        let trash = Trash::new(dir.path().join("trash"));
+        let path = dir.path().join("50% done #1");
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("inside.txt"), "x").unwrap();
+
+        let item = trash.put(&LocalFs, &path).unwrap();
+        assert!(item.is_dir);
+        let listed = trash.list(&LocalFs).unwrap();
+        assert_eq!(listed[0].original_path, path);
+
+        trash.restore(&LocalFs, &listed[0]).unwrap();
+        assert_eq!(std::fs::read_to_string(path.join("inside.txt")).unwrap(), "x");
+    }
+
+    #[test]
+    fn items_with_the_same_name_are_kept_apart() {
+        let dir = TempDir::new();
+        let trash = Trash::new(dir.path().join("trash"));
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "first").unwrap();
+        let first = trash.put(&LocalFs, &path).unwrap();
+        std::fs::write(&path, "second").unwrap();
+        let second = trash.put(&LocalFs, &path).unwrap();
+
+        assert_eq!(second.trashed_path.file_name().unwrap(), "a.2.txt");
+        assert_eq!(std::fs::read_to_string(&first.trashed_path).unwrap(), "first");
+    }
+
+    #[test]
+    fn restore_refuses_to_replace_what_appeared_since() {
+        let dir = TempDir::new();
+        let trash = Trash::new(dir.path().join("trash"));
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "old").unwrap();
+        let item = trash.put(&LocalFs, &path).unwrap();
+        std::fs::write(&path, "new").unwrap();
+
+        assert!(matches!(trash.restore(&LocalFs, &item), Err(FsError::AlreadyExists(_))));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    }
+
+    #[test]
+    fn purge_deletes_for_good() {
+        let dir = TempDir::new();
+        let trash = Trash::new(dir.path().join("trash"));
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "").unwrap();
+        let item = trash.put(&LocalFs, &path).unwrap();
+
+        trash.purge(&LocalFs, &item).unwrap();
+        assert!(!item.trashed_path.exists());
+        assert!(trash.list(&LocalFs).unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn names_that_are_not_utf8_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = TempDir::new();
+        let trash = Trash::new(dir.path().join("trash"));
+        let path = dir.path().join(OsStr::from_bytes(b"caf\xe9.txt"));
+        std::fs::write(&path, "x").unwrap();
+
+        let item = trash.put(&LocalFs, &path).unwrap();
+        assert_eq!(trash.list(&LocalFs).unwrap(), vec![item.clone()]);
+
+        assert_eq!(trash.restore(&LocalFs, &item).unwrap(), path);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "x");
+        assert!(trash.list(&LocalFs).unwrap().is_empty());
+    }
+
+    #[test]
+    fn paths_survive_escaping() {
+        let path = Path::new("/home/me/50% off/naïve #1.txt");
+        assert_eq!(encode_path(path), "/home/me/50%25%20off/na%C3%AFve%20%231.txt");
+        assert_eq!(decode_path(&encode_path(path)), path);
+    }
+}
@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::dnd;
+use crate::navigation::Navigation;
+use crate::notifications::Notice;
+use crate::settings::Settings;
+use crate::transfer::TransferRequest;
+
+// Folders to jump back to, saved with the settings. Ctrl+D bookmarks the folder
+// being shown; they're listed above the folder tree and under ★ in the path bar.
+pub struct BookmarksPlugin;
+
+impl Plugin for BookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, bookmark_shortcut);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Bookmark {
+    pub label: String,
+    pub path: PathBuf,
+}
+
+// In the order they're listed
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct Bookmarks(Vec<Bookmark>);
+
+impl Bookmarks {
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.0.iter()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.0.iter().any(|bookmark| bookmark.path == path)
+    }
+
+    // Adds `path` at the end, labelled with its folder name. Returns false if it was already there.
+    // settings.ron can only hold UTF-8 paths, so others are refused instead of breaking every later save.
+    pub fn add(&mut self, path: &Path) -> Result<bool, String> {
+        if path.to_str().is_none() {
+            return Err(format!("Can't bookmark {}: its name isn't valid UTF-8", path.display()));
+        }
+        if self.contains(path) {
+            return Ok(false);
+        }
+        let label = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+        self.0.push(Bookmark { label, path: path.to_path_buf() });
+        Ok(true)
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.0.retain(|bookmark| bookmark.path != path);
+    }
+}
+
+// What was done to a bookmark in the sidebar
+enum Edit {
+    Relabel(usize, String),
+    Move { from: usize, to: usize },
+    Remove(usize),
+}
+
+// The bookmark whose label is being edited in the sidebar
+#[derive(Default)]
+pub struct LabelEditor {
+    editing: Option<(usize, String)>,
+    focus: bool,  // Focus the field on the next frame
+}
+
+// What clicking or dropping on a bookmark in the sidebar asks for
+pub enum BookmarkAction {
+    Open(PathBuf),
+    Drop(Result<TransferRequest, String>),
+}
+
+// The bookmarks above the folder tree: click to open, drop items onto them, and
+// right-click to rename, reorder or remove one
+pub fn bookmarks_section(
+    ui: &mut egui::Ui,
+    settings: &mut ResMut<Settings>,
+    labels: &mut LabelEditor,
+    current: &Path,
+) -> Option<BookmarkAction> {
+    let mut action = None;
+    let mut edit = None;
+    let count = settings.bookmarks.0.len();
+
+    egui::CollapsingHeader::new("Bookmarks").default_open(true).show(ui, |ui| {
+        if count == 0 {
+            ui.weak("Ctrl+D bookmarks this folder");
+        }
+        for (index, bookmark) in settings.bookmarks.iter().enumerate() {
+            if let Some((_, label)) = labels.editing.as_mut().filter(|(editing, _)| *editing == index) {
+                let field = ui.text_edit_singleline(label);
+                if std::mem::take(&mut labels.focus) {
+                    field.request_focus();
+                }
+                if field.lost_focus() {
+                    if !ui.input(|i| i.key_pressed(egui::Key::Escape)) && !label.trim().is_empty() {
+                        edit = Some(Edit::Relabel(index, label.trim().to_owned()));
+                    }
+                    labels.editing = None;
+                }
+                continue;
+            }
+
+            let row = ui.selectable_label(bookmark.path == current, format!("★ {}", bookmark.label)).on_hover_text(bookmark.path.display().to_string());
+            if row.clicked() {
+                action = Some(BookmarkAction::Open(bookmark.path.clone()));
+            }
+            if let Some(dropped) = dnd::drop_target(&row, &bookmark.path) {
+                action = Some(BookmarkAction::Drop(dropped));
+            }
+            row.context_menu(|ui| {
+                if ui.button("Rename").clicked() {
+                    labels.editing = Some((index, bookmark.label.clone()));
+                    labels.focus = true;
+                    ui.close_menu();
+                }
+                if ui.add_enabled(index > 0, egui::Button::new("Move Up")).clicked() {
+                    edit = Some(Edit::Move { from: index, to: index - 1 });
+                    ui.close_menu();
+                }
+                if ui.add_enabled(index + 1 < count, egui::Button::new("Move Down")).clicked() {
+                    edit = Some(Edit::Move { from: index, to: index + 1 });
+                    ui.close_menu();
+                }
+                if ui.button("Remove Bookmark").clicked() {
+                    edit = Some(Edit::Remove(index));
+                    ui.close_menu();
+                }
+            });
+        }
+    });
+
+    // Only touched when something changed, so the settings aren't saved every frame
+    if let Some(edit) = edit {
+        let bookmarks = &mut settings.bookmarks.0;
+        match edit {
+            Edit::Relabel(index, label) => bookmarks[index].label = label,
+            Edit::Move { from, to } => bookmarks.swap(from, to),
+            Edit::Remove(index) => {
+                bookmarks.remove(index);
+            }
+        }
+    }
+    action
+}
+
+// The ★ menu in the path bar: every bookmark, and bookmarking the folder being shown.
+// Returns the bookmark to open.
+pub fn bookmarks_menu(
+    ui: &mut egui::Ui,
+    settings: &mut ResMut<Settings>,
+    current: &Path,
+    notices: &mut EventWriter<Notice>,
+) -> Option<PathBuf> {
+    let mut open = None;
+    ui.menu_button("★", |ui| {
+        if settings.bookmarks.contains(current) {
+            if ui.button("Remove Bookmark").clicked() {
+                settings.bookmarks.remove(current);
+                ui.close_menu();
+            }
+        } else if ui.add(egui::Button::new("Bookmark This Folder").shortcut_text("Ctrl+D")).clicked() {
+            if let Err(e) = settings.bookmarks.add(current) {
+                notices.send(Notice::error(e));
+            }
+            ui.close_menu();
+        }
+        if settings.bookmarks.0.is_empty() {
+            return;
+        }
+        ui.separator();
+        for bookmark in settings.bookmarks.iter() {
+            if ui.button(&bookmark.label).on_hover_text(bookmark.path.display().to_string()).clicked() {
+                open = Some(bookmark.path.clone());
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Bookmarks");
+    open
+}
+
+// Ctrl+D bookmarks the folder being shown, unless a text field wants the keys
+fn bookmark_shortcut(
+    mut contexts: EguiContexts,
+    navigation: Res<Navigation>,
+    mut settings: ResMut<Settings>,
+    mut notices: EventWriter<Notice>,
+) {
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_keyboard_input() || !ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::D)) {
+        return;
+    }
+    let dir = navigation.current();
+    match settings.bookmarks.add(dir) {
+        Ok(true) => {
+            notices.send(Notice::info(format!("Bookmarked {}", dir.display())));
+        }
+        Ok(false) => {}
+        Err(e) => {
+            notices.send(Notice::error(e));
+        }
+    }
+}
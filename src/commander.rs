@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::path::Path;
+
+use crate::listing::DirectoryListing;
+use crate::navigation::Navigation;
+use crate::notifications::Notice;
+use crate::selection::Selection;
+use crate::transfer::{TransferMode, TransferRequest};
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// The commander view: two folders side by side, like Norton or Midnight Commander.
+// The active pane is the one in the `Navigation`, `Selection` and `DirectoryListing`
+// resources, so the path bar, the folder tree and the shortcuts all work on it; the
+// other pane is kept here and swapped in when it's clicked. F5 copies the selection
+// to the other pane's folder, F6 moves it there.
+pub struct CommanderPlugin;
+
+impl Plugin for CommanderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Commander>()
+            .add_systems(Update, commander_shortcuts)
+            .add_systems(PostUpdate, drive_other_pane);  // Like the listing, after the UI asked
+    }
+}
+
+// The folder, history, selection and listing of the pane that isn't active
+pub struct Pane {
+    pub navigation: Navigation,
+    pub selection: Selection,
+    pub listing: DirectoryListing,
+}
+
+#[derive(Resource, Default)]
+pub struct Commander {
+    open: bool,  // Whether the view is split; View > Dual Pane
+    other: Option<Pane>,  // Kept while the view isn't split, so it comes back where it was
+    other_on_left: bool,
+}
+
+impl Commander {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+        if let Some(pane) = self.other.as_mut().filter(|_| open) {
+            pane.listing.invalidate();  // Nothing kept it up to date while it was hidden
+        }
+    }
+
+    // The pane that isn't active, opened on the active pane's folder the first time
+    pub fn other_pane(&mut self, navigation: &Navigation) -> &mut Pane {
+        self.other.get_or_insert_with(|| Pane {
+            navigation: navigation.fresh(navigation.current()),
+            selection: Selection::default(),
+            listing: DirectoryListing::default(),
+        })
+    }
+
+    // The other pane's folder, while the view is split
+    pub fn other_dir(&self) -> Option<&Path> {
+        self.other.as_ref().filter(|_| self.open).map(|pane| pane.navigation.current())
+    }
+
+    pub fn other_on_left(&self) -> bool {
+        self.other_on_left
+    }
+
+    // Makes the other pane the active one. Both stay on their side of the window.
+    pub fn swap(&mut self, navigation: &mut Navigation, selection: &mut Selection, listing: &mut DirectoryListing) {
+        let Some(other) = self.other.as_mut() else {
+            return;
+        };
+        std::mem::swap(navigation, &mut other.navigation);
+        std::mem::swap(selection, &mut other.selection);
+        std::mem::swap(listing, &mut other.listing);
+        self.other_on_left = !self.other_on_left;
+    }
+}
+
+// The folder name above a pane, highlighted on the active one. Clicking it activates the pane.
+pub fn pane_header(ui: &mut egui::Ui, dir: &Path, active: bool) -> egui::Response {
+    let name = dir.file_name().unwrap_or(dir.as_os_str()).to_string_lossy();
+    ui.selectable_label(active, format!("🗀 {}", name)).on_hover_text(dir.display().to_string())
+}
+
+// F5 copies the active pane's selection into the other pane's folder, F6 moves it.
+// Only while the view is split, and not while typing.
+fn commander_shortcuts(
+    mut contexts: EguiContexts,
+    commander: Res<Commander>,
+    selection: Res<Selection>,
+    mut transfer_requests: EventWriter<TransferRequest>,
+) {
+    let Some(dest) = commander.other_dir() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let (copy, move_) = ctx.input_mut(|i| {
+        (i.consume_key(egui::Modifiers::NONE, egui::Key::F5), i.consume_key(egui::Modifiers::NONE, egui::Key::F6))
+    });
+    let mode = match (copy, move_) {
+        (true, _) => TransferMode::Copy,
+        (_, true) => TransferMode::Move,
+        _ => return,
+    };
+    if selection.is_empty() {
+        return;
+    }
+    transfer_requests.send(TransferRequest {
+        sources: selection.items(),
+        dest: dest.to_path_buf(),
+        mode,
+        from_outside: false,
+    });
+}
+
+// Keeps the other pane's listing up to date, like `ListingPlugin` does for the active one
+fn drive_other_pane(
+    storage: Res<Storage>,
+    mut commander: ResMut<Commander>,
+    mut changes: EventReader<FsChange>,
+    mut notices: EventWriter<Notice>,
+) {
+    let commander = &mut *commander;
+    let Some(pane) = commander.other.as_mut().filter(|_| commander.open) else {
+        changes.clear();
+        return;
+    };
+    for change in changes.read() {
+        pane.listing.apply_change(&*storage.0, change);
+    }
+    if let Some(e) = pane.listing.drive_scan(&storage.0) {
+        notices.send(Notice::error(format!("Couldn't read folder: {}", e)));
+    }
+}
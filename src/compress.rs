@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::archive;
+use file_manager_core::error::Result as FsResult;
+use file_manager_core::ops::Progress;
+use file_manager_core::Operation;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::history::History;
+use crate::notifications::Notice;
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// Archives that finish quicker than this don't flash a progress window
+const PROGRESS_DELAY: Duration = Duration::from_millis(300);
+
+// "Compress" in the item menus: packs the items into a zip archive next to them.
+// Archives are put together on the async compute pool, one at a time.
+pub struct CompressPlugin;
+
+impl Plugin for CompressPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Compressions>()
+            .add_event::<CompressRequest>()
+            .add_systems(Update, (queue_compressions, run_compressions, compress_window).chain());
+    }
+}
+
+// Pack these items, all from the same folder, into one archive
+#[derive(Event, Clone, Debug)]
+pub struct CompressRequest(pub Vec<PathBuf>);
+
+// An archive being put together in the background
+struct Job {
+    items: Vec<PathBuf>,
+    to: PathBuf,
+    progress: Arc<Progress>,
+    started: Instant,
+    task: Task<FsResult<()>>,
+}
+
+#[derive(Resource, Default)]
+struct Compressions {
+    queue: VecDeque<Vec<PathBuf>>,
+    running: Option<Job>,
+}
+
+fn queue_compressions(mut requests: EventReader<CompressRequest>, mut compressions: ResMut<Compressions>) {
+    for CompressRequest(items) in requests.read() {
+        if !items.is_empty() {
+            compressions.queue.push_back(items.clone());
+        }
+    }
+}
+
+// Picks up the finished archive, then starts on the next one
+fn run_compressions(
+    storage: Res<Storage>,
+    mut history: ResMut<History>,
+    mut compressions: ResMut<Compressions>,
+    mut fs_changes: EventWriter<FsChange>,
+    mut notices: EventWriter<Notice>,
+) {
+    let fs = &*storage.0;
+    let Compressions { queue, running } = &mut *compressions;
+
+    if let Some(mut job) = running.take() {
+        match block_on(future::poll_once(&mut job.task)) {
+            None => {
+                *running = Some(job);
+                return;
+            }
+            // Recorded only now that it's done, so Ctrl+Z can trash the archive
+            Some(Ok(())) => {
+                info!("Compressed {:?} into {:?}", job.items, job.to);
+                history.0.record(fs, Operation::Compress { items: job.items, to: job.to.clone(), trashed: None });
+                fs_changes.send(FsChange::Created(job.to));
+            }
+            Some(Err(e)) => {
+                notices.send(Notice::error(format!("Couldn't compress: {}", e)));
+            }
+        }
+    }
+
+    let Some(items) = queue.pop_front() else {
+        return;
+    };
+    let Some(to) = archive::archive_path(fs, &items) else {
+        return;  // Only a root folder has no folder to put its archive in
+    };
+    let progress = Arc::new(Progress::default());
+    let task = {
+        let (fs, items, to, progress) = (storage.0.clone(), items.clone(), to.clone(), progress.clone());
+        AsyncComputeTaskPool::get().spawn(async move { archive::compress(&*fs, &items, &to, &progress) })
+    };
+    *running = Some(Job { items, to, progress, started: Instant::now(), task });
+}
+
+// Shows how far the archive is once it has taken a moment
+fn compress_window(mut contexts: EguiContexts, compressions: Res<Compressions>) {
+    let Some(job) = compressions.running.as_ref().filter(|job| job.started.elapsed() >= PROGRESS_DELAY) else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    egui::Window::new("Compress")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -20.0))
+        .show(ctx, |ui| {
+            let (done, total) = (job.progress.done(), job.progress.total().max(1));
+            let name = job.to.file_name().unwrap_or_default().to_string_lossy();
+            ui.label(format!("Compressing into {}: {} of {} files", name, done, total));
+            ui.add(egui::ProgressBar::new(done as f32 / total as f32).desired_width(300.0));
+        });
+    ctx.request_repaint();  // Keep the bar moving while nothing else happens
+}
@@ -0,0 +1,73 @@
+use bevy_egui::egui;
+use file_manager_core::ops;
+use std::path::{Path, PathBuf};
+
+use crate::transfer::{TransferMode, TransferRequest};
+
+// What's carried while grid items are dragged around
+pub struct DraggedItems(pub Vec<PathBuf>);
+
+// Starts dragging `items` when `response` begins a drag
+pub fn drag_source(response: &egui::Response, items: impl FnOnce() -> Vec<PathBuf>) {
+    if response.drag_started() {
+        response.dnd_set_drag_payload(DraggedItems(items()));
+    }
+}
+
+// Shows how many items are being dragged next to the pointer
+pub fn drag_preview(ctx: &egui::Context) {
+    let Some(dragged) = egui::DragAndDrop::payload::<DraggedItems>(ctx) else {
+        return;
+    };
+    let text = match dragged.0.as_slice() {
+        [only] => only.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        items => format!("{} items", items.len()),
+    };
+    egui::show_tooltip_at_pointer(ctx, egui::LayerId::background(), egui::Id::new("drag_preview"), |ui| {
+        ui.label(text);
+    });
+}
+
+// Makes `response` a place to drop dragged items into the folder `dir`: they're moved,
+// or copied while Ctrl is held. The target is outlined while something hovers over it.
+// Returns the transfer to start on release, or why the drop was refused.
+// Only needs the response, so it also works for table rows, which have no `Ui` of their own.
+pub fn drop_target(response: &egui::Response, dir: &Path) -> Option<Result<TransferRequest, String>> {
+    let hovering = response.dnd_hover_payload::<DraggedItems>()?;
+    let refusal = refusal(&hovering.0, dir);
+
+    let ctx = &response.ctx;
+    let style = ctx.style();
+    let color = if refusal.is_some() { style.visuals.error_fg_color } else { style.visuals.selection.stroke.color };
+    // Clipped to the visible part of the target, so it doesn't spill out of a scroll area
+    let painter = ctx.layer_painter(response.layer_id).with_clip_rect(response.interact_rect.expand(4.0));
+    painter.rect_stroke(response.rect.expand(3.0), 6.0, egui::Stroke::new(2.0, color));
+    if let Some(refusal) = &refusal {
+        egui::show_tooltip_at_pointer(ctx, response.layer_id, response.id.with("refused_drop"), |ui| {
+            ui.colored_label(color, refusal);
+        });
+    }
+
+    let dropped = response.dnd_release_payload::<DraggedItems>()?;
+    if let Some(refusal) = refusal {
+        return Some(Err(refusal));
+    }
+    let mode = if ctx.input(|i| i.modifiers.command) { TransferMode::Copy } else { TransferMode::Move };
+    Some(Ok(TransferRequest {
+        sources: dropped.0.clone(),
+        dest: dir.to_path_buf(),
+        mode,
+        from_outside: false,
+    }))
+}
+
+// Why `items` can't go into `dir`: a folder can't be put inside itself
+fn refusal(items: &[PathBuf], dir: &Path) -> Option<String> {
+    let item = items.iter().find(|item| ops::check_not_into_itself(item, dir).is_err())?;
+    let name = item.file_name().unwrap_or_default().to_string_lossy();
+    Some(if item == dir {
+        format!("Can't move \"{}\" into itself", name)
+    } else {
+        format!("Can't move \"{}\" into one of its own subfolders", name)
+    })
+}
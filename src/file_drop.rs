@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::listing::DirectoryListing;
+use crate::transfer::{TransferMode, TransferRequest};
+
+// Files and folders dragged in from the desktop or another program are copied into
+// the current folder, or moved when Shift is held as they're dropped.
+pub struct FileDropPlugin;
+
+impl Plugin for FileDropPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HoveringFiles>()
+            .add_systems(Update, (import_dropped_files, drop_hint).chain());
+    }
+}
+
+// Whether files from outside are being dragged over the window
+#[derive(Resource, Default)]
+struct HoveringFiles(bool);
+
+fn import_dropped_files(
+    mut events: EventReader<FileDragAndDrop>,
+    keys: Res<ButtonInput<KeyCode>>,
+    listing: Res<DirectoryListing>,
+    mut hovering: ResMut<HoveringFiles>,
+    mut requests: EventWriter<TransferRequest>,
+) {
+    let mut dropped = Vec::new();
+    for event in events.read() {
+        match event {
+            FileDragAndDrop::HoveredFile { .. } => hovering.0 = true,
+            FileDragAndDrop::HoveredFileCanceled { .. } => hovering.0 = false,
+            FileDragAndDrop::DroppedFile { path_buf, .. } => {
+                hovering.0 = false;
+                dropped.push(path_buf.clone());  // One event per file, all in the same frame
+            }
+        }
+    }
+    if dropped.is_empty() {
+        return;
+    }
+
+    // A single request, so "Apply to all" in the collision dialog covers the whole drop
+    let mode = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        TransferMode::Move
+    } else {
+        TransferMode::Copy
+    };
+    requests.send(TransferRequest {
+        sources: dropped,
+        dest: listing.dir().to_path_buf(),
+        mode,
+        from_outside: true,
+    });
+}
+
+// Tells where hovering files will end up
+fn drop_hint(mut contexts: EguiContexts, hovering: Res<HoveringFiles>, listing: Res<DirectoryListing>) {
+    if !hovering.0 {
+        return;
+    }
+    egui::Area::new(egui::Id::new("drop_hint"))
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .order(egui::Order::Foreground)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(format!("Drop to copy into {}", listing.dir().display()));
+                ui.weak("Hold Shift to move instead");
+            });
+        });
+}
@@ -0,0 +1,311 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::{ops, FsError};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::bookmarks::{self, BookmarkAction, LabelEditor};
+use crate::compress::CompressRequest;
+use crate::dnd;
+use crate::grid::{self, MenuChoice};
+use crate::history::History;
+use crate::navigation::Navigation;
+use crate::notifications::Notice;
+use crate::rename::InlineRename;
+use crate::selection::Selection;
+use crate::settings::Settings;
+use crate::sounds::{PlaySound, SoundEvent};
+use crate::tabs::TabAction;
+use crate::transfer::{Clipboard, TransferRequest};
+use crate::view;
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// The folder tree on the left, starting at the workspace root. Folders are read when
+// they're expanded, in the background like the grid's listing, and the tree opens up
+// to wherever the grid is showing. View > Folder Tree hides it.
+pub struct FolderTreePlugin;
+
+impl Plugin for FolderTreePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FolderTree>()
+            .add_systems(PostUpdate, (refresh_changed_folders, load_folders).chain());  // Like the listing, after the UI asked
+    }
+}
+
+type LoadResult = (PathBuf, Result<Vec<PathBuf>, FsError>);
+
+#[derive(Resource)]
+pub struct FolderTree {
+    pub open: bool,  // Whether the panel is showing
+    children: HashMap<PathBuf, Vec<PathBuf>>,  // Subfolders of every folder read so far, in natural name order
+    expanded: HashSet<PathBuf>,
+    wanted: HashSet<PathBuf>,  // Expanded or changed folders that need (re)reading
+    loads: HashMap<PathBuf, Task<LoadResult>>,  // Reads running in the background
+    shown: PathBuf,  // The grid's folder the tree last opened up to
+    scroll_to_shown: bool,  // Until its row has been scrolled into view
+}
+
+impl Default for FolderTree {
+    fn default() -> Self {
+        Self {
+            open: true,
+            children: HashMap::new(),
+            expanded: HashSet::new(),
+            wanted: HashSet::new(),
+            loads: HashMap::new(),
+            shown: PathBuf::new(),
+            scroll_to_shown: false,
+        }
+    }
+}
+
+impl FolderTree {
+    fn expand(&mut self, dir: &Path) {
+        if self.expanded.insert(dir.to_path_buf()) && !self.children.contains_key(dir) {
+            self.wanted.insert(dir.to_path_buf());
+        }
+    }
+
+    // Opens every folder from `root` down to `dir`, so `dir` can be seen
+    fn reveal(&mut self, root: &Path, dir: &Path) {
+        let Ok(below) = dir.strip_prefix(root) else {
+            return;  // Outside the workspace, nothing in the tree to show
+        };
+        let mut ancestor = root.to_path_buf();
+        self.expand(&ancestor);
+        for component in below.parent().into_iter().flat_map(Path::components) {
+            ancestor.push(component);
+            self.expand(&ancestor);
+        }
+    }
+
+    // The folders on screen with their depth, in drawing order
+    fn rows(&self, root: &Path) -> Vec<(PathBuf, usize)> {
+        let mut rows = Vec::new();
+        let mut stack = vec![(root.to_path_buf(), 0)];
+        while let Some((dir, depth)) = stack.pop() {
+            if self.expanded.contains(&dir) {
+                if let Some(children) = self.children.get(&dir) {
+                    stack.extend(children.iter().rev().map(|child| (child.clone(), depth + 1)));
+                }
+            }
+            rows.push((dir, depth));
+        }
+        rows
+    }
+}
+
+// Draws the tree. Runs after the path bar and before `ui_system`, so it sits under the top panels.
+#[allow(clippy::too_many_arguments)]
+pub fn folder_tree_panel(
+    storage: Res<Storage>,
+    mut contexts: EguiContexts,
+    mut tree: ResMut<FolderTree>,
+    mut navigation: ResMut<Navigation>,
+    mut selection: ResMut<Selection>,
+    mut clipboard: ResMut<Clipboard>,
+    mut history: ResMut<History>,
+    mut transfer_requests: EventWriter<TransferRequest>,
+    mut fs_changes: EventWriter<FsChange>,
+    mut notices: EventWriter<Notice>,
+    mut sounds: EventWriter<PlaySound>,
+    mut tab_actions: EventWriter<TabAction>,
+    mut compress_requests: EventWriter<CompressRequest>,
+    mut settings: ResMut<Settings>,
+    mut labels: Local<LabelEditor>,
+    mut inline_rename: Local<InlineRename>,
+) {
+    let root = navigation.workspace().to_path_buf();
+    let current = navigation.current().to_path_buf();
+    if tree.shown != current {
+        tree.reveal(&root, &current);
+        tree.shown = current.clone();
+        tree.scroll_to_shown = true;
+    }
+    let tree = &mut *tree;  // Lets the panel borrow fields separately
+    let mut open_dir = None;
+    let mut renamed = None;
+
+    egui::SidePanel::left("folder_tree").resizable(true).default_width(180.0).show_animated(contexts.ctx_mut(), tree.open, |ui| {
+        egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false).show(ui, |ui| {
+            match bookmarks::bookmarks_section(ui, &mut settings, &mut labels, &current) {
+                Some(BookmarkAction::Open(dir)) => open_dir = Some(dir),
+                Some(BookmarkAction::Drop(Ok(request))) => {
+                    transfer_requests.send(request);
+                }
+                Some(BookmarkAction::Drop(Err(refusal))) => {
+                    notices.send(Notice::error(refusal));
+                }
+                None => {}
+            }
+            ui.separator();
+
+            for (dir, depth) in tree.rows(&root) {
+                ui.horizontal(|ui| {
+                    ui.add_space(depth as f32 * 14.0);
+
+                    // Folders that turned out to be empty lose their arrow
+                    let is_leaf = tree.children.get(&dir).is_some_and(Vec::is_empty);
+                    let expanded = tree.expanded.contains(&dir);
+                    if is_leaf {
+                        ui.add_space(ui.spacing().icon_width);
+                    } else if ui.add(egui::Button::new(if expanded { "⏷" } else { "⏵" }).frame(false)).clicked() {
+                        if expanded {
+                            tree.expanded.remove(&dir);
+                        } else {
+                            tree.expand(&dir);
+                        }
+                    }
+
+                    if inline_rename.is_renaming(&dir) {
+                        if let Some(names) = inline_rename.show(ui, &*storage.0) {
+                            renamed = Some(names);
+                        }
+                        return;
+                    }
+                    let name = if dir == root { String::from("🏠") } else { dir.file_name().unwrap_or_default().to_string_lossy().into_owned() };
+                    let row = ui.add(egui::SelectableLabel::new(dir == current, format!("🗀 {}", name))).interact(egui::Sense::drag());
+                    if dir == current && std::mem::take(&mut tree.scroll_to_shown) {
+                        row.scroll_to_me(Some(egui::Align::Center));  // Once its parents have been read
+                    }
+                    if row.clicked() {
+                        open_dir = Some(dir.clone());
+                    }
+                    if row.middle_clicked() {
+                        tab_actions.send(TabAction::OpenInBackground(dir.clone()));
+                    }
+                    dnd::drag_source(&row, || vec![dir.clone()]);
+                    match dnd::drop_target(&row, &dir) {
+                        Some(Ok(request)) => {
+                            transfer_requests.send(request);
+                        }
+                        Some(Err(refusal)) => {
+                            notices.send(Notice::error(refusal));
+                        }
+                        None => {}
+                    }
+
+                    // The same menu as a folder in the grid, for just this folder
+                    row.context_menu(|ui| {
+                        if ui.button("Open").clicked() {
+                            open_dir = Some(dir.clone());
+                            ui.close_menu();
+                        }
+                        let bookmarked = settings.bookmarks.contains(&dir);
+                        match grid::item_menu(ui, true, 1, dir != root, bookmarked, &clipboard) {
+                            Some(MenuChoice::OpenInNewTab) => {
+                                tab_actions.send(TabAction::OpenInBackground(dir.clone()));
+                            }
+                            Some(MenuChoice::Rename) => inline_rename.start(&dir),
+                            Some(MenuChoice::Copy) => clipboard.copy(vec![dir.clone()]),
+                            Some(MenuChoice::Cut) => clipboard.cut(vec![dir.clone()]),
+                            Some(MenuChoice::PasteInto) => {
+                                transfer_requests.send(clipboard.paste(&dir));
+                            }
+                            Some(MenuChoice::Bookmark) => {
+                                if let Err(e) = settings.bookmarks.add(&dir) {
+                                    notices.send(Notice::error(e));
+                                }
+                            }
+                            Some(MenuChoice::Compress) => {
+                                compress_requests.send(CompressRequest(vec![dir.clone()]));
+                            }
+                            Some(MenuChoice::Trash) => match history.0.delete(&*storage.0, &dir) {
+                                Ok(_) => {
+                                    sounds.send(PlaySound(SoundEvent::Delete));
+                                    fs_changes.send(FsChange::Removed(dir.clone()));
+                                    if current.starts_with(&dir) {
+                                        open_dir = dir.parent().map(Path::to_path_buf);  // Don't stay in a folder that's gone
+                                    }
+                                }
+                                Err(e) => {
+                                    notices.send(Notice::error(format!("Couldn't move to trash: {}", e)));
+                                }
+                            },
+                            None => {}
+                        }
+                    });
+                });
+            }
+        });
+    });
+
+    if let Some((from, to)) = renamed {
+        match history.0.rename(&*storage.0, &from, &to) {  // Recorded, so Ctrl+Z puts the old name back
+            Ok(()) => {
+                fs_changes.send(FsChange::Renamed { from: from.clone(), to: to.clone() });
+                if let Ok(inside) = current.strip_prefix(&from) {
+                    open_dir = Some(to.join(inside));  // Follow the folder being shown to its new name
+                }
+            }
+            Err(e) => {
+                notices.send(Notice::error(format!("Couldn't rename: {}", e)));
+            }
+        }
+    }
+
+    if let Some(dir) = open_dir {
+        if navigation.open(&dir) {
+            selection.clear();
+            sounds.send(PlaySound(SoundEvent::OpenFolder));
+        }
+    }
+}
+
+// Folders that changed are read again if the tree has them
+fn refresh_changed_folders(mut tree: ResMut<FolderTree>, mut changes: EventReader<FsChange>) {
+    for change in changes.read() {
+        let changed: Vec<&Path> = match change {
+            FsChange::Created(path) | FsChange::Removed(path) => path.parent().into_iter().collect(),
+            FsChange::Renamed { from, to } => from.parent().into_iter().chain(to.parent()).collect(),
+            FsChange::Rescan(dir) => vec![dir.as_path()],
+            FsChange::Modified(_) => Vec::new(),  // Content changes don't add or remove folders
+        };
+        for dir in changed {
+            if tree.children.contains_key(dir) {
+                tree.wanted.insert(dir.to_path_buf());
+            }
+        }
+    }
+}
+
+// Starts reading wanted folders and collects the finished reads
+fn load_folders(storage: Res<Storage>, mut tree: ResMut<FolderTree>, mut notices: EventWriter<Notice>) {
+    let tree = &mut *tree;
+    for dir in tree.wanted.drain() {
+        let fs = storage.0.clone();
+        let key = dir.clone();
+        // Replacing an older read of the same folder drops it, so its stale result never lands
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let result = ops::list_directory(&*fs, &dir).map(|entries| {
+                let mut folders: Vec<PathBuf> = entries.into_iter().filter(|entry| entry.is_dir).map(|entry| entry.path).collect();
+                folders.sort_by(|a, b| view::natural_cmp(&a.file_name().unwrap_or_default().to_string_lossy(), &b.file_name().unwrap_or_default().to_string_lossy()));
+                folders
+            });
+            (dir, result)
+        });
+        tree.loads.insert(key, task);
+    }
+
+    let mut finished = Vec::new();
+    tree.loads.retain(|_, task| match block_on(future::poll_once(task)) {
+        Some(result) => {
+            finished.push(result);
+            false
+        }
+        None => true,
+    });
+    for (dir, result) in finished {
+        match result {
+            Ok(folders) => {
+                tree.children.insert(dir, folders);
+            }
+            Err(e) => {
+                tree.children.insert(dir, Vec::new());  // Shown as empty rather than asked for again every frame
+                notices.send(Notice::error(format!("Couldn't read folder: {}", e)));
+            }
+        }
+    }
+}
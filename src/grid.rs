@@ -0,0 +1,408 @@
+use bevy_egui::egui;
+use chrono::Local;
+use egui_extras::{Column, TableBuilder};
+use file_manager_core::{Entry, FileSystem, ThumbnailSize};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::bookmarks::Bookmarks;
+use crate::dnd;
+use crate::listing::DirectoryListing;
+use crate::rename::InlineRename;
+use crate::selection::Selection;
+use crate::thumbnails::Thumbnails;
+use crate::transfer::{Clipboard, TransferRequest};
+use crate::view::{self, FolderView, Grouping, Sort, ViewMode};
+
+// What the user did to the items in a grid, for `ui_system` to carry out. Clicks
+// that only change the selection are applied to the grid's `Selection` directly.
+pub enum GridAction {
+    OpenFolder(PathBuf),
+    OpenFile(PathBuf),
+    OpenInNewTab(PathBuf),
+    Copy(Vec<PathBuf>),
+    Cut(Vec<PathBuf>),
+    PasteInto(PathBuf),
+    Bookmark(PathBuf),
+    Trash(Vec<PathBuf>),
+    Compress(Vec<PathBuf>),
+    Transfer(TransferRequest),  // Items dropped on a folder
+    Refused(String),  // A drop that can't be done, and why
+    Rename { from: PathBuf, to: PathBuf },
+    Sort { dir: PathBuf, sort: Sort },  // A column header was clicked
+    Zoom(f32),  // The icon size Ctrl+scroll asked for
+}
+
+// The outlined box with the files and folders in `listing`, as icons or as a details
+// table depending on `view`: click to select, drag to move, and a context menu on
+// every item. Both panes of the commander view draw their folder with this.
+#[allow(clippy::too_many_arguments)]
+pub fn item_grid(
+    ui: &mut egui::Ui,
+    fs: &dyn FileSystem,
+    listing: &DirectoryListing,
+    view: FolderView,
+    icon_size: f32,
+    selection: &mut Selection,
+    inline_rename: &mut InlineRename,
+    clipboard: &Clipboard,
+    bookmarks: &Bookmarks,
+    thumbnails: &mut Thumbnails,
+) -> Vec<GridAction> {
+    let mut items = Items { entries: listing.entries(), selection, inline_rename, clipboard, bookmarks, thumbnails, actions: Vec::new() };
+
+    // Use a group styled with a frame for the outlined container
+    egui::Frame::none()
+        .stroke(egui::Stroke::new(1.0, egui::Color32::BLACK)) // Black outline
+        .fill(egui::Color32::WHITE) // White background
+        .inner_margin(egui::vec2(10.0, 10.0)) // Inner padding
+        .rounding(egui::Rounding::same(10.0)) // Rounded corners
+        .show(ui, |ui| {
+            if listing.is_loading() {
+                ui.spinner();  // The background scan hasn't finished yet
+            } else if let Some(error) = listing.error() {
+                ui.colored_label(ui.visuals().error_fg_color, format!("Couldn't read this folder: {}", error));
+            }
+            match view.mode {
+                ViewMode::Icons => icons(ui, fs, view.grouping, icon_size, &mut items),
+                ViewMode::Details => details(ui, fs, listing.dir(), view.sort, &mut items),
+            }
+        });
+
+    items.actions
+}
+
+// The thumbnail of an image once it's ready, the folder or plain file icon otherwise
+fn icon(entry: &Entry, thumbnail: Option<&egui::TextureHandle>) -> egui::ImageSource<'static> {
+    match thumbnail {
+        Some(texture) => texture.into(),
+        None if entry.is_dir => egui::include_image!("assets/folder.png"),
+        None => egui::include_image!("assets/file.png"),
+    }
+}
+
+// One line of the icon grid: a section header, or a row of items
+enum Line {
+    Header(String),
+    Items(Range<usize>),  // Indices into the entries
+}
+
+// Splits `entries` into rows of `columns` items, starting a new row under a header
+// wherever a section begins. The listing keeps each section together.
+fn lines(entries: &[Entry], grouping: Grouping, columns: usize) -> Vec<Line> {
+    let today = Local::now().date_naive();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < entries.len() {
+        let section = grouping.section(&entries[start], today);
+        let end = entries[start..].iter().position(|entry| grouping.section(entry, today) != section).map_or(entries.len(), |length| start + length);
+        lines.extend(section.map(Line::Header));
+        lines.extend((start..end).step_by(columns).map(|row| Line::Items(row..(row + columns).min(end))));
+        start = end;
+    }
+    lines
+}
+
+// The icons with their names underneath, as many to a row as fit, in sections under
+// headers when grouped. Only the rows in view are laid out, so large folders scroll
+// smoothly. Ctrl+scroll zooms the icons.
+fn icons(ui: &mut egui::Ui, fs: &dyn FileSystem, grouping: Grouping, icon_size: f32, items: &mut Items) {
+    // Add the ScrollArea for the files and folders list
+    egui::ScrollArea::vertical() // Makes the container scrollable vertically
+        .auto_shrink([false, true]) // Only shrink horizontally; keep the vertical scrolling
+        .drag_to_scroll(false)  // Dragging draws a selection rectangle instead
+        .show_viewport(ui, |ui, viewport| {
+            // Every item gets the same cell, wide enough for a few letters of its name even when small
+            let spacing = ui.spacing().item_spacing;
+            let label_height = ui.spacing().interact_size.y;
+            let cell = egui::vec2(icon_size.max(64.0) + 8.0, icon_size + label_height + 8.0);
+            let columns = (((ui.available_width() + spacing.x) / (cell.x + spacing.x)).floor() as usize).max(1);
+            let line_height = |line: &Line| match line {
+                Line::Header(_) => label_height + spacing.y,
+                Line::Items(_) => cell.y + spacing.y,
+            };
+            let lines = lines(items.entries, grouping, columns);
+            ui.set_min_height(lines.iter().map(line_height).sum());  // The whole grid's height, for the scroll bar
+            let origin = ui.max_rect().min;
+
+            if ui.rect_contains_pointer(ui.clip_rect()) {
+                let zoom = ui.input(|i| i.zoom_delta());
+                if zoom != 1.0 {
+                    items.actions.push(GridAction::Zoom(icon_size * zoom));
+                }
+            }
+
+            // Dragging over the grid selects everything the rectangle touches, Ctrl adds to the selection
+            let band = ui.interact(ui.clip_rect(), ui.id().with("rubber_band"), egui::Sense::drag());
+            if band.drag_started() {
+                if let Some(origin) = band.interact_pointer_pos() {
+                    items.selection.start_band(origin, ui.input(|i| i.modifiers.command));
+                }
+            }
+            let mut item_rects = Vec::new();  // Where every item is, on screen or not, while the rectangle is dragged
+
+            let mut top = 0.0;
+            for line in &lines {
+                let height = line_height(line);
+                let visible = top + height >= viewport.min.y && top <= viewport.max.y;
+                let line_origin = origin + egui::vec2(0.0, top);
+                top += height;
+
+                match line {
+                    Line::Header(title) if visible => {
+                        let rect = egui::Rect::from_min_size(line_origin, egui::vec2(ui.available_width(), label_height));
+                        ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| ui.label(egui::RichText::new(title).strong()));
+                    }
+                    Line::Header(_) => {}
+                    Line::Items(row) => {
+                        for (column, entry) in items.entries[row.clone()].iter().enumerate() {
+                            let rect = egui::Rect::from_min_size(line_origin + egui::vec2(column as f32 * (cell.x + spacing.x), 0.0), cell);
+                            if band.dragged() {
+                                item_rects.push((entry.path.clone(), rect));
+                            }
+                            if visible {
+                                icon_cell(ui, fs, rect, entry, icon_size, items);
+                            }
+                        }
+                    }
+                }
+            }
+
+            if band.dragged() {
+                if let Some(rect) = band.interact_pointer_pos().and_then(|pointer| items.selection.update_band(pointer, &item_rects)) {
+                    let stroke = ui.visuals().selection.stroke;
+                    ui.painter().rect(rect, 0.0, ui.visuals().selection.bg_fill.gamma_multiply(0.2), stroke);
+                }
+            }
+            if band.drag_stopped() {
+                items.selection.end_band();
+            }
+        });
+}
+
+// One item of the icon grid in `rect`: its icon, with its name or the rename field underneath
+fn icon_cell(ui: &mut egui::Ui, fs: &dyn FileSystem, rect: egui::Rect, entry: &Entry, icon_size: f32, items: &mut Items) {
+    if items.selection.is_selected(&entry.path) {
+        let fill = ui.visuals().selection.bg_fill.gamma_multiply(0.4);
+        ui.painter().rect_filled(rect.expand(2.0), 6.0, fill);
+    }
+    // Salted with the path, so widgets keep their IDs while rows scroll in and out of view
+    ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect).id_salt(&entry.path), |ui| {
+        ui.vertical_centered(|ui| {
+            let thumbnail = items.thumbnails.get(entry, ThumbnailSize::for_edge(icon_size));
+            // Centred in a square, so names stay in line under thumbnails that aren't square
+            let logo = ui.add_sized(
+                [icon_size, icon_size],
+                egui::ImageButton::new(
+                    egui::Image::new(icon(entry, thumbnail.as_ref()))
+                        .tint(items.tint(&entry.path))
+                        .fit_to_exact_size(egui::vec2(icon_size, icon_size)),
+                )
+                .frame(false),
+            )
+            .interact(egui::Sense::drag());  // Dragged onto a folder, it moves there
+            items.interact(&logo, entry);
+
+            if items.inline_rename.is_renaming(&entry.path) {
+                items.rename_field(ui, fs);
+            } else {
+                ui.add(egui::Label::new(entry.name()).truncate());  // The full name shows on hover
+            }
+        });
+    });
+}
+
+// One row per item with its size, type, times and permissions. Columns can be resized,
+// and clicking a header sorts by it, clicking it again reverses the order.
+fn details(ui: &mut egui::Ui, fs: &dyn FileSystem, dir: &Path, sort: Sort, items: &mut Items) {
+    // Plain text that leaves clicks and drags to the row
+    fn cell(ui: &mut egui::Ui, text: impl Into<egui::WidgetText>) {
+        ui.add(egui::Label::new(text).selectable(false).truncate());
+    }
+
+    let entries = items.entries;
+    let mut sort_by = None;
+    TableBuilder::new(ui)
+        .id_salt("details")
+        .striped(true)
+        .resizable(true)
+        .sense(egui::Sense::click_and_drag())  // Rows select, drag and open like icons
+        .drag_to_scroll(false)
+        .auto_shrink([false, true])
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::initial(220.0).at_least(80.0).clip(true))
+        .column(Column::initial(80.0).at_least(50.0))
+        .column(Column::initial(90.0).at_least(50.0).clip(true))
+        .column(Column::initial(120.0).at_least(60.0))
+        .column(Column::initial(120.0).at_least(60.0))
+        .column(Column::remainder().at_least(80.0))
+        .header(22.0, |mut header| {
+            for (key, title) in view::SORT_KEYS {
+                header.col(|ui| {
+                    let arrow = match sort.key == key {
+                        true if sort.descending => " ⏷",
+                        true => " ⏶",
+                        false => "",
+                    };
+                    if ui.add(egui::Button::new(egui::RichText::new(format!("{}{}", title, arrow)).strong()).frame(false)).clicked() {
+                        sort_by = Some(sort.toggled(key));
+                    }
+                });
+            }
+        })
+        .body(|body| {
+            body.rows(20.0, entries.len(), |mut row| {
+                let entry = &entries[row.index()];
+                row.set_selected(items.selection.is_selected(&entry.path));
+                row.col(|ui| {
+                    let thumbnail = items.thumbnails.get(entry, ThumbnailSize::Normal);
+                    ui.add(egui::Image::new(icon(entry, thumbnail.as_ref())).tint(items.tint(&entry.path)).fit_to_exact_size(egui::vec2(16.0, 16.0)));
+                    if items.inline_rename.is_renaming(&entry.path) {
+                        items.rename_field(ui, fs);
+                    } else {
+                        cell(ui, entry.name());
+                    }
+                });
+                row.col(|ui| {
+                    if !entry.is_dir {
+                        cell(ui, view::size_text(entry.len));
+                    }
+                });
+                row.col(|ui| cell(ui, view::type_name(entry)));
+                row.col(|ui| cell(ui, view::time_text(entry.modified)));
+                row.col(|ui| cell(ui, view::time_text(entry.created)));
+                row.col(|ui| cell(ui, egui::RichText::new(view::permissions_text(entry.mode)).monospace()));
+                items.interact(&row.response(), entry);
+            });
+        });
+
+    if let Some(sort) = sort_by {
+        items.actions.push(GridAction::Sort { dir: dir.to_path_buf(), sort });
+    }
+}
+
+// What the items of either view share: how they react to the mouse and their menu
+struct Items<'a> {
+    entries: &'a [Entry],
+    selection: &'a mut Selection,
+    inline_rename: &'a mut InlineRename,
+    clipboard: &'a Clipboard,
+    bookmarks: &'a Bookmarks,
+    thumbnails: &'a mut Thumbnails,
+    actions: Vec<GridAction>,
+}
+
+impl Items<'_> {
+    // Cut items are dimmed until pasted
+    fn tint(&self, path: &Path) -> egui::Color32 {
+        if self.clipboard.is_cut(path) { egui::Color32::from_white_alpha(110) } else { egui::Color32::WHITE }
+    }
+
+    // The name field of the item being renamed
+    fn rename_field(&mut self, ui: &mut egui::Ui, fs: &dyn FileSystem) {
+        if let Some((from, to)) = self.inline_rename.show(ui, fs) {
+            self.actions.push(GridAction::Rename { from, to });
+        }
+    }
+
+    // Selecting, dragging, dropping onto and opening the item `response` belongs to, and its context menu
+    fn interact(&mut self, response: &egui::Response, entry: &Entry) {
+        let item = &entry.path;
+        dnd::drag_source(response, || self.selection.targets(item));
+        if entry.is_dir {
+            // Items dropped on a folder move into it, or are copied with Ctrl
+            match dnd::drop_target(response, item) {
+                Some(Ok(request)) => self.actions.push(GridAction::Transfer(request)),
+                Some(Err(refusal)) => self.actions.push(GridAction::Refused(refusal)),
+                None => {}
+            }
+        }
+
+        // A click selects, Ctrl-click toggles, Shift-click selects a range
+        if response.clicked() {
+            self.selection.click(item, response.ctx.input(|i| i.modifiers), self.entries);
+        }
+
+        // A double click opens folders and files
+        if response.double_clicked() {
+            self.actions.push(if entry.is_dir { GridAction::OpenFolder(item.clone()) } else { GridAction::OpenFile(item.clone()) });
+        }
+
+        // A middle click opens a folder in a new tab
+        if response.middle_clicked() && entry.is_dir {
+            self.actions.push(GridAction::OpenInNewTab(item.clone()));
+        }
+
+        // Right-clicking outside the selection makes the menu about this item alone
+        if response.secondary_clicked() && !self.selection.is_selected(item) {
+            self.selection.click(item, egui::Modifiers::NONE, self.entries);
+        }
+
+        // Item context menu, acting on the whole selection
+        response.context_menu(|ui| {
+            let targets = self.selection.targets(item);
+            let action = match item_menu(ui, entry.is_dir, targets.len(), true, self.bookmarks.contains(item), self.clipboard) {
+                Some(MenuChoice::OpenInNewTab) => GridAction::OpenInNewTab(item.clone()),
+                Some(MenuChoice::Rename) => {
+                    self.inline_rename.start(item);  // Edit the name in place
+                    return;
+                }
+                Some(MenuChoice::Copy) => GridAction::Copy(targets),
+                Some(MenuChoice::Cut) => GridAction::Cut(targets),  // Moved when pasted
+                Some(MenuChoice::PasteInto) => GridAction::PasteInto(item.clone()),
+                Some(MenuChoice::Bookmark) => GridAction::Bookmark(item.clone()),
+                Some(MenuChoice::Compress) => GridAction::Compress(targets),
+                Some(MenuChoice::Trash) => GridAction::Trash(targets),
+                None => return,
+            };
+            self.actions.push(action);
+        });
+    }
+}
+
+// What was picked in an item's context menu
+pub enum MenuChoice {
+    OpenInNewTab,
+    Rename,
+    Copy,
+    Cut,
+    PasteInto,
+    Bookmark,
+    Compress,
+    Trash,
+}
+
+// The context menu of an item in the grid or a folder in the tree, acting on `count` items.
+// `movable` is false for the workspace root, which has no folder of its own to be renamed,
+// cut, compressed or trashed in.
+pub fn item_menu(ui: &mut egui::Ui, is_dir: bool, count: usize, movable: bool, bookmarked: bool, clipboard: &Clipboard) -> Option<MenuChoice> {
+    let mut choice = None;
+    if is_dir && ui.button("Open in New Tab").clicked() {
+        choice = Some(MenuChoice::OpenInNewTab);
+    }
+    if ui.add_enabled(movable && count == 1, egui::Button::new("Rename").shortcut_text("F2")).clicked() {
+        choice = Some(MenuChoice::Rename);
+    }
+    if ui.button("Copy").clicked() {
+        choice = Some(MenuChoice::Copy);
+    }
+    if ui.add_enabled(movable, egui::Button::new("Cut")).clicked() {
+        choice = Some(MenuChoice::Cut);
+    }
+    if is_dir && ui.add_enabled(!clipboard.is_empty(), egui::Button::new("Paste Into Folder")).clicked() {
+        choice = Some(MenuChoice::PasteInto);
+    }
+    if is_dir && ui.add_enabled(!bookmarked, egui::Button::new("Add to Bookmarks")).clicked() {
+        choice = Some(MenuChoice::Bookmark);
+    }
+    if ui.add_enabled(movable, egui::Button::new("Compress")).clicked() {
+        choice = Some(MenuChoice::Compress);
+    }
+    if ui.add_enabled(movable, egui::Button::new("Move to Trash")).clicked() {
+        choice = Some(MenuChoice::Trash);
+    }
+    if choice.is_some() {
+        ui.close_menu();
+    }
+    choice
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::Journal;
+
+use crate::jail::JailRoot;
+use crate::listing::DirectoryListing;
+use crate::notifications::Notice;
+use crate::trash_view::TrashBin;
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// Undo/redo for everything done through the context menus, with Ctrl+Z / Ctrl+Shift+Z
+// and an Edit > History window listing every step.
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HistoryView>()
+            .add_event::<HistoryAction>()
+            .add_systems(Startup, create_journal)
+            .add_systems(Update, (history_shortcuts, history_window, apply_history_actions).chain())
+            .add_systems(Last, remove_backups);
+    }
+}
+
+// The journal every file operation in the UI goes through
+#[derive(Resource)]
+pub struct History(pub Journal);
+
+// Whether the Edit > History window is showing
+#[derive(Resource, Default)]
+pub struct HistoryView {
+    pub open: bool,
+}
+
+// A request to move through the history, from the menu, a shortcut or the History window
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryAction {
+    Undo,
+    Redo,
+    JumpTo(usize),  // Leave exactly this many records in effect
+}
+
+// Builds the journal on top of the trash set up by `TrashPlugin`
+fn create_journal(mut commands: Commands, trash: Res<TrashBin>, jail: Res<JailRoot>) {
+    // Saved-over content only has to outlive this session
+    let backup_name = format!("file_manger-history-{}", std::process::id());
+    let backup_dir = jail.data_dir().unwrap_or_else(std::env::temp_dir).join(backup_name);
+    commands.insert_resource(History(Journal::new(trash.0.clone(), backup_dir)));
+}
+
+// Saved-over content is only kept for undo, so it goes when the app does
+fn remove_backups(mut exits: EventReader<AppExit>, storage: Res<Storage>, history: Res<History>) {
+    if exits.read().last().is_none() {
+        return;
+    }
+    if let Err(e) = history.0.remove_backups(&*storage.0) {
+        error!("Couldn't remove the undo backups: {}", e);
+    }
+}
+
+// Ctrl+Z undoes, Ctrl+Shift+Z redoes, unless a text field has focus and wants them itself
+fn history_shortcuts(mut contexts: EguiContexts, mut actions: EventWriter<HistoryAction>) {
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let (undo, redo) = ctx.input_mut(|i| {
+        // Check the shifted combination first, plain Ctrl+Z would match it too
+        let redo = i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z);
+        let undo = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z);
+        (undo, redo)
+    });
+    if redo {
+        actions.send(HistoryAction::Redo);
+    } else if undo {
+        actions.send(HistoryAction::Undo);
+    }
+}
+
+// Lists the journal; steps in effect can be reverted, undone ones redone
+fn history_window(
+    mut contexts: EguiContexts,
+    history: Res<History>,
+    mut view: ResMut<HistoryView>,
+    mut actions: EventWriter<HistoryAction>,
+) {
+    if !view.open {
+        return;
+    }
+    let journal = &history.0;
+
+    egui::Window::new("History")
+        .open(&mut view.open)
+        .default_size(egui::vec2(380.0, 300.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(journal.can_undo(), egui::Button::new("Undo")).clicked() {
+                    actions.send(HistoryAction::Undo);
+                }
+                if ui.add_enabled(journal.can_redo(), egui::Button::new("Redo")).clicked() {
+                    actions.send(HistoryAction::Redo);
+                }
+            });
+            ui.separator();
+
+            if journal.records().is_empty() {
+                ui.label("Nothing has been done yet.");
+                return;
+            }
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                egui::Grid::new("history_records").striped(true).num_columns(3).show(ui, |ui| {
+                    for (index, record) in journal.records().iter().enumerate().rev() {
+                        let applied = index < journal.applied();
+                        let description = egui::RichText::new(record.operation.describe());
+                        // Undone steps are greyed out until they're redone or replaced
+                        ui.label(if applied { description } else { description.weak().italics() });
+                        ui.label(record.done_at.format("%H:%M:%S").to_string());
+                        if applied {
+                            if ui.button("Revert").on_hover_text("Undo this and every later step").clicked() {
+                                actions.send(HistoryAction::JumpTo(index));
+                            }
+                        } else if ui.button("Redo").on_hover_text("Redo every step up to this one").clicked() {
+                            actions.send(HistoryAction::JumpTo(index + 1));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
+
+// Performs the requested undo/redo steps and has the listing pick up the result
+fn apply_history_actions(
+    storage: Res<Storage>,
+    listing: Res<DirectoryListing>,
+    mut history: ResMut<History>,
+    mut actions: EventReader<HistoryAction>,
+    mut fs_changes: EventWriter<FsChange>,
+    mut notices: EventWriter<Notice>,
+) {
+    let fs = &*storage.0;
+    for action in actions.read() {
+        let result = match action {
+            HistoryAction::Undo => history.0.undo(fs).map(|_| ()),
+            HistoryAction::Redo => history.0.redo(fs).map(|_| ()),
+            HistoryAction::JumpTo(applied) => history.0.jump_to(fs, *applied),
+        };
+        if let Err(e) = result {
+            notices.send(Notice::error(format!("Couldn't undo/redo: {}", e)));
+        }
+        // Undo can touch paths anywhere (trash, other folders), so just re-read what's on screen
+        fs_changes.send(FsChange::Rescan(listing.dir().to_path_buf()));
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use file_manager_core::{JailedFs, LocalFs};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::settings::Settings;
+use crate::Storage;
+
+// Sets up `Storage`: the whole local disk, or with `jail: Some("...")` in settings.ron,
+// only that folder. Everything the app does to files goes through `Storage`, so the
+// jail covers navigation, editing, copying, trashing and undo alike.
+// There's deliberately no way to change it from inside the app.
+pub struct JailPlugin;
+
+impl Plugin for JailPlugin {
+    fn build(&self, app: &mut App) {
+        let root = app.world().resource::<Settings>().jail.clone();
+        let (storage, root) = match root {
+            Some(root) => {
+                let _ = std::fs::create_dir_all(&root);  // Otherwise it would refuse everything, which is still safe
+                let jailed = JailedFs::new(LocalFs, &root);
+                let root = jailed.root().to_path_buf();
+                (Storage(Arc::new(jailed)), Some(root))
+            }
+            None => (Storage(Arc::new(LocalFs)), None),
+        };
+        app.insert_resource(storage).insert_resource(JailRoot(root));
+    }
+}
+
+// The folder the app is limited to, with symlinks resolved, if there is one
+#[derive(Resource)]
+pub struct JailRoot(pub Option<PathBuf>);
+
+impl JailRoot {
+    // Where the app keeps its own files (the trash, undo backups) when it can't leave the root
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.0.as_ref().map(|root| root.join(".file_manger"))
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use chrono::Local;
+use file_manager_core::{ops, Entry, FileSystem, FsError};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::notifications::Notice;
+use crate::view::{Grouping, Sort};
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// Scans directories on the async compute pool and keeps the result cached,
+// so drawing the grid never touches the disk.
+pub struct ListingPlugin;
+
+impl Plugin for ListingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DirectoryListing>()
+            .add_event::<FsChange>()
+            .add_systems(PostUpdate, (apply_changes, drive_scans).chain()); // Runs after the UI so requests start the same frame
+    }
+}
+
+type ScanResult = (PathBuf, Result<Vec<Entry>, FsError>);
+
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);  // Shared, so no two listings ever have the same revision
+
+// The cached contents of the directory being shown
+#[derive(Resource, Default)]
+pub struct DirectoryListing {
+    dir: PathBuf,  // Directory the UI asked for
+    entries: Vec<Entry>,  // Folders first, then files, as of the last finished scan of `dir`
+    error: Option<String>,  // Why `dir` couldn't be read, shown instead of the entries
+    stale: bool,  // Set when `dir` changed or something invalidated the cache
+    scan: Option<Task<ScanResult>>,  // The scan currently running in the background
+    sort: Sort,  // The order the folder's view asks for,
+    grouping: Grouping,  // within the sections it's split into
+    revision: u64,  // Changes whenever the entries do, see `revision()`
+}
+
+impl DirectoryListing {
+    // Shows `dir`, scanning it only if it differs from what is already displayed
+    pub fn navigate(&mut self, dir: &Path) {
+        if self.dir != dir {
+            self.dir = dir.to_path_buf();
+            self.stale = true;
+            // Never show, or act on, another folder's items under this folder's path
+            self.entries.clear();
+            self.error = None;
+            self.touch();
+        }
+    }
+
+    // Forces a rescan of the current directory, e.g. when watcher events were lost
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    // Keeps the entries grouped by `grouping`, and in `sort`'s order within each group
+    pub fn arrange(&mut self, sort: Sort, grouping: Grouping) {
+        if (self.sort, self.grouping) != (sort, grouping) {
+            (self.sort, self.grouping) = (sort, grouping);
+            self.resort();
+        }
+    }
+
+    fn resort(&mut self) {
+        let today = Local::now().date_naive();
+        let (sort, grouping) = (self.sort, self.grouping);
+        self.entries.sort_by(|a, b| grouping.compare(a, b, today).then_with(|| sort.compare(a, b)));
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    // Changes whenever entries are added, updated or dropped, so the selection and the new item
+    // dialog know when to check again without comparing against every entry each frame
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn touch(&mut self) {
+        self.revision = NEXT_REVISION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Why the folder couldn't be read, if the last scan failed
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    // True while a background scan hasn't come back yet
+    pub fn is_loading(&self) -> bool {
+        self.stale || self.scan.is_some()
+    }
+
+    // Patches the entries for one change, if it happened in the folder being shown
+    pub fn apply_change(&mut self, fs: &dyn FileSystem, change: &FsChange) {
+        // A scan in flight may have read the folder before this change, so its result can't be trusted
+        if self.scan.is_some() {
+            self.invalidate();
+            return;
+        }
+
+        let dir = self.dir.clone();
+        let in_view = |path: &Path| path.parent() == Some(dir.as_path());
+        match change {
+            FsChange::Created(path) | FsChange::Modified(path) if in_view(path) => {
+                match fs.metadata(path) {
+                    Ok(entry) => self.upsert(entry),
+                    Err(_) => self.remove(path),  // Already gone again
+                }
+            }
+            FsChange::Removed(path) if in_view(path) => self.remove(path),
+            FsChange::Renamed { from, to } => {
+                if in_view(from) {
+                    self.remove(from);
+                }
+                if in_view(to) {
+                    if let Ok(entry) = fs.metadata(to) {
+                        self.upsert(entry);
+                    }
+                }
+            }
+            FsChange::Rescan(rescan) if *rescan == dir => self.invalidate(),
+            _ => {}  // Somewhere we're not looking
+        }
+    }
+
+    // Starts a scan when stale and picks up the result once it's done.
+    // Returns why the scan failed; the listing is then empty and shows the error.
+    pub fn drive_scan(&mut self, fs: &Arc<dyn FileSystem>) -> Option<FsError> {
+        if self.stale {
+            self.stale = false;
+            let fs = fs.clone();
+            let dir = self.dir.clone();
+            // Replacing the old task drops it, which cancels a scan of a folder we already left
+            self.scan = Some(AsyncComputeTaskPool::get().spawn(async move {
+                let result = ops::list_directory(&*fs, &dir);
+                (dir, result)
+            }));
+        }
+
+        let (dir, result) = block_on(future::poll_once(self.scan.as_mut()?))?;  // None while still scanning
+        self.scan = None;
+        if dir != self.dir {
+            return None;  // A newer navigation already replaced this scan
+        }
+        self.touch();
+        match result {
+            Ok(entries) => {
+                self.entries = entries;
+                self.error = None;
+                self.resort();
+                None
+            }
+            Err(e) => {
+                self.entries.clear();  // What was listed may be gone, e.g. the folder was deleted
+                self.error = Some(e.to_string());
+                Some(e)
+            }
+        }
+    }
+
+    // Adds or refreshes one entry where the order puts it; a new size or time can move it
+    fn upsert(&mut self, entry: Entry) {
+        self.remove(&entry.path);
+        let today = Local::now().date_naive();
+        let (sort, grouping) = (self.sort, self.grouping);
+        let position = self.entries.partition_point(|e| grouping.compare(e, &entry, today).then_with(|| sort.compare(e, &entry)).is_lt());
+        self.entries.insert(position, entry);
+    }
+
+    fn remove(&mut self, path: &Path) {
+        self.entries.retain(|e| e.path != path);
+        self.touch();
+    }
+}
+
+// Patches the cached entries from `FsChange` events instead of rescanning the folder
+fn apply_changes(
+    storage: Res<Storage>,
+    mut listing: ResMut<DirectoryListing>,
+    mut changes: EventReader<FsChange>,
+) {
+    for change in changes.read() {
+        listing.apply_change(&*storage.0, change);
+    }
+}
+
+// Starts a scan when the listing is stale and picks up the result once it's done
+fn drive_scans(storage: Res<Storage>, mut listing: ResMut<DirectoryListing>, mut notices: EventWriter<Notice>) {
+    if let Some(e) = listing.drive_scan(&storage.0) {
+        notices.send(Notice::error(format!("Couldn't read folder: {}", e)));
+    }
+}
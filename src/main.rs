@@ -1,9 +1,68 @@
-use bevy::{prelude::*, window::WindowResolution};
+use bevy::{ecs::system::SystemParam, prelude::*, window::WindowResolution};
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
-use rand::Rng;
-use std::fs::{self, File};
-use std::io::{self, Read, Write};
-use std::path::{Path, PathBuf};
+use file_manager_core::{ops, FileSystem};
+use listing::{DirectoryListing, ListingPlugin};
+use std::path::PathBuf;
+use std::sync::Arc;
+use history::{History, HistoryPlugin};
+use jail::JailPlugin;
+use rename::InlineRename;
+use selection::Selection;
+use bookmarks::BookmarksPlugin;
+use commander::{Commander, CommanderPlugin};
+use compress::{CompressPlugin, CompressRequest};
+use file_drop::FileDropPlugin;
+use folder_tree::FolderTreePlugin;
+use grid::GridAction;
+use settings::{Settings, SettingsPlugin};
+use sounds::{PlaySound, SoundEvent, SoundsPlugin};
+use navigation::{Navigation, NavigationPlugin};
+use new_item::{NewItem, NewItemDialog, NewItemKind, NewItemPlugin, Templates};
+use notifications::{Notice, NotificationsPlugin};
+use tabs::{TabAction, TabsPlugin};
+use thumbnails::{Thumbnails, ThumbnailsPlugin};
+use trash_view::{TrashPlugin, TrashView};
+use transfer::{Clipboard, TransferPlugin, TransferRequest};
+use view::{FolderView, IconSize, ViewPlugin};
+use watcher::{FsChange, WatcherPlugin};
+
+mod dnd;
+mod bookmarks;
+mod commander;
+mod compress;
+mod file_drop;
+mod folder_tree;
+mod grid;
+mod history;
+mod jail;
+mod listing;
+mod menu;
+mod navigation;
+mod new_item;
+mod notifications;
+mod path_bar;
+mod rename;
+mod selection;
+mod settings;
+mod sounds;
+mod tabs;
+mod thumbnails;
+mod trash_view;
+mod transfer;
+mod view;
+mod watcher;
+
+// The file system every UI action goes through (the real disk unless swapped out)
+#[derive(Resource, Clone)]
+struct Storage(Arc<dyn FileSystem>);
+
+// The text editor window for an opened or newly created file
+#[derive(Default)]
+struct Editor {
+    text: String,  // The content being edited
+    file: Option<PathBuf>,  // Where Save writes it
+    open: bool,
+}
 
 
 fn main() {
@@ -20,22 +79,63 @@ fn main() {
         }))
        // .add_plugins(DefaultPlugins)  // Adds default plugins (audio, window, etc.)
         .add_plugins(EguiPlugin)  // Adds Egui plugin for UI functionality
-        .insert_resource(ClearColor(Color::rgb(214.0 / 255.0, 204.0 / 255.0, 185.0 / 255.0))) // Set the background color of the window
-        .add_systems(Update, ui_system) // Register the UI update system
+        .add_plugins(NotificationsPlugin)  // Toasts and an error log instead of stderr
+        .add_plugins(SettingsPlugin)  // Preferences saved in the config folder
+        .add_plugins(JailPlugin)  // The local disk as `Storage`, optionally limited to one folder
+        .add_plugins(NavigationPlugin)  // Back/Forward/Up/Home between folders
+        .add_plugins(TabsPlugin)  // Several folders open in tabs
+        .add_plugins(CommanderPlugin)  // Two folders side by side, F5/F6 copy or move between them
+        .add_plugins(ListingPlugin)  // Scans the current directory in the background
+        .add_plugins(FolderTreePlugin)  // The folder tree on the left
+        .add_plugins(WatcherPlugin)  // Picks up changes other programs make to the current directory
+        .add_plugins(TrashPlugin)  // Deleted items go to the trash and can be restored
+        .add_plugins(HistoryPlugin)  // Undo/redo for every file operation
+        .add_plugins(NewItemPlugin)  // Templates for the Create File dialog
+        .add_plugins(TransferPlugin)  // Copy, cut and paste
+        .add_plugins(CompressPlugin)  // Zip archives of the selected items
+        .add_plugins(FileDropPlugin)  // Files dropped from other programs are copied in
+        .add_plugins(SoundsPlugin)  // Audio feedback
+        .add_plugins(BookmarksPlugin)  // Ctrl+D bookmarks the current folder
+        .add_plugins(ViewPlugin)  // Icons or details, remembered per folder
+        .add_plugins(ThumbnailsPlugin)  // Thumbnails of images in place of the file icon
+        .init_resource::<Selection>()  // Which grid items are selected
+        .insert_resource(ClearColor(Color::srgb(214.0 / 255.0, 204.0 / 255.0, 185.0 / 255.0))) // Set the background color of the window
+        .add_systems(Update, (menu::menu_bar, tabs::tab_bar, path_bar::path_bar_panel, folder_tree::folder_tree_panel, ui_system).chain()) // Register the menu bar, tab bar, path bar, folder tree and the UI update system
         .run(); // Run the application
 }
 
+// Events `ui_system` sends, bundled to stay within Bevy's limit on system parameters
+#[derive(SystemParam)]
+struct GridEvents<'w> {
+    fs_changes: EventWriter<'w, FsChange>,  // Tells the listing about our own file operations
+    notices_writer: EventWriter<'w, Notice>,  // Reports failures to the user
+    transfer_requests: EventWriter<'w, TransferRequest>,  // Pastes, worked through by `TransferPlugin`
+    sounds: EventWriter<'w, PlaySound>,  // Audio feedback for opening and deleting
+    tab_actions: EventWriter<'w, TabAction>,  // Folders opened in new tabs
+    compress_requests: EventWriter<'w, CompressRequest>,  // Archives to make, worked through by `CompressPlugin`
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ui_system(
 
+    storage: Res<Storage>,  // File system used for every file operation
     mut contexts: EguiContexts,  // Access the Egui context for UI updates
-    mut input_text: Local<String>,  // Holds the text content for file operations
-    mut files_and_folders: Local<Vec<PathBuf>>,  // Holds files and folders in the current directory
-    mut loaded_file: Local<Option<PathBuf>>,  // Holds the path of the currently loaded file
-    mut current_dir_str: Local<String>,  // Holds the current directory as a string
-    mut show_file_popup: Local<bool>,  // Flag to show the file creation popup
-    mut show_folder_popup: Local<bool>,  // Flag to show the folder creation popup
-    mut show_save_popup: Local<bool>,  // Flag to show the save popup for file content
+    mut editor: Local<Editor>,  // The file open in the editor window
+    mut listing: ResMut<DirectoryListing>,  // Cached files and folders of the current directory
+    mut selection: ResMut<Selection>,  // Selected grid items, what the item menus act on
+    events: GridEvents,  // Changes, notices, transfers and sounds the grid sends out
+    mut history: ResMut<History>,  // Records every file operation so it can be undone
+    mut trash_view: ResMut<TrashView>,  // The Trash window
+    mut clipboard: ResMut<Clipboard>,  // Items copied or cut from the grid
+    templates: Res<Templates>,  // Starting content offered for new files
+    mut navigation: ResMut<Navigation>,  // The current directory and the Back/Forward history
+    mut new_item_dialog: Local<NewItemDialog>,  // Asks for the name of a new file or folder
+    mut inline_rename: Local<InlineRename>,  // The grid item whose name is being edited, if any
+    mut settings: ResMut<Settings>,  // Bookmarks are added from the menus
+    mut commander: ResMut<Commander>,  // The second pane of the dual-pane view
+    mut thumbnails: ResMut<Thumbnails>,  // Thumbnails of the images in the grid
 ) {
+    let GridEvents { mut fs_changes, mut notices_writer, mut transfer_requests, mut sounds, mut tab_actions, mut compress_requests } = events;
 
 
     let ctx = contexts.ctx_mut();  // Get mutable reference to the Egui context
@@ -43,69 +143,40 @@ fn ui_system(
     // Initialize image loaders for Egui (if needed)
     egui_extras::install_image_loaders(ctx);
 
-    // If the current directory string is empty, set it to "./root"
-    if current_dir_str.is_empty() {
-        *current_dir_str = String::from("./root");
-    }
-
-    let dir_path = Path::new(current_dir_str.as_str());  // Convert current directory string to Path
-
-    // Read the directory contents if the directory exists
-    if dir_path.exists() {
-        let entries = match fs::read_dir(dir_path) {
-            Ok(entries) => entries,
-            Err(e) => {
-                eprintln!("Error reading directory {}", e);
-                return;
-            }
-        };
-
-        let mut file_paths = Vec::new();  // Vector to store file paths
-        let mut folder_paths = Vec::new();  // Vector to store folder paths
-
-        // Loop through the entries in the directory and categorize them into files and folders
-        for entry in entries.filter_map(|entry| entry.ok()) {
-            let path = entry.path();
-            if path.is_file() {
-                file_paths.push(path);
-            } else if path.is_dir() {
-                folder_paths.push(path);
+    // Ask the cached listing for the directory in the path bar; it only rescans when this changes
+    listing.navigate(navigation.current());
+    let view = settings.folder_views.get(navigation.current());  // Icons or details, and the order
+    listing.arrange(view.sort, view.grouping);
+    let icon_size = settings.icon_size.get();
+    let mut changes = Vec::new();  // What our own file operations changed, applied to the listing after drawing
+    let mut notices = Vec::new();  // Failures to show as toasts
+    let mut renames = Vec::new();  // Confirmed inline renames, as (from, to)
+    let mut grid_actions = Vec::new();  // What was done to the items, carried out after drawing
+    let mut switch_panes = false;  // The other commander pane was clicked
+    selection.retain_listed(&listing);  // Forget items that were deleted or moved away
+
+    // Ctrl+A selects everything, F2 renames the selected item, Escape deselects; not while typing
+    if !ctx.wants_keyboard_input() {
+        let (select_all, rename, deselect) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::A),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::F2),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if select_all {
+            selection.select_all(listing.entries());
+        }
+        if rename {
+            if let [only] = selection.items().as_slice() {
+                inline_rename.start(only);
             }
         }
-
-        // Concatenate the folders and files into one list
-        *files_and_folders = [folder_paths, file_paths].concat();
+        if deselect {
+            selection.clear();
+        }
     }
 
-    egui::TopBottomPanel::top("top_panel")
-    .exact_height(50.0) // Set height to 50 px
-    .frame(
-        egui::Frame::none()
-            .fill(egui::Color32::from_rgb(153, 153, 153)) // Set background color to #999999
-            .inner_margin(egui::Margin::same(10.0)),   // Add some padding
-    )
-    .show(ctx, |ui| {
-        ui.vertical_centered(|ui| {
-            ui.add_space((50.0 - 20.0) / 10.0); 
-
-            egui::Frame::none()
-                .fill(egui::Color32::WHITE) 
-                .rounding(egui::Rounding::same(15.0))
-                .inner_margin(egui::Margin::symmetric(10.0, 5.0)) 
-                .stroke(egui::Stroke::new(1.0, egui::Color32::BLACK)) 
-                .show(ui, |ui| {
-                    ui.allocate_ui(egui::vec2(500.0, ui.available_height()), |ui| {
-                        ui.add(
-                            egui::TextEdit::singleline(&mut *current_dir_str)
-                                .frame(false) 
-                                .desired_width(500.0), 
-                        );
-                    });
-                });
-        });
-    });
-
-
     // Central panel to show the main UI
     egui::CentralPanel::default()
         .frame(egui::Frame::default().inner_margin(egui::vec2(50.0, 10.0))) // Add inner margin
@@ -113,141 +184,96 @@ fn ui_system(
             ui.vertical_centered(|ui| { 
 
                     ui.add_space(100.0);
-                    // Detect right-click on the blank area of the panel
-                    ui.interact(ui.max_rect(), ui.id(), egui::Sense::click()).context_menu(|ui| {
+                    // Detect clicks on the blank area of the panel
+                    let background = ui.interact(ui.max_rect(), ui.id(), egui::Sense::click());
+                    if background.clicked() {
+                        selection.clear();  // Clicking next to the items deselects them
+                    }
+                    background.context_menu(|ui| {
                         if ui.button("Create File").clicked() {
-                            *show_file_popup = true; // Show the file creation popup
-                            *input_text = String::new(); // Clear the input text
+                            new_item_dialog.open(NewItemKind::File);  // Ask for a name, extension and template
                             ui.close_menu(); // Close the context menu
                         }
+                        if ui.add_enabled(!clipboard.is_empty(), egui::Button::new("Paste")).clicked() {
+                            transfer_requests.send(clipboard.paste(navigation.current()));  // Into the current folder
+                            ui.close_menu();
+                        }
+                        if ui.button("Show Trash").clicked() {
+                            trash_view.open();  // Browse, restore or purge deleted items
+                            ui.close_menu();
+                        }
                         if ui.button("Create Folder").clicked() {
-                            let random_folder_name = format!("folder_{}", generate_random_number());
-                            create_folder(&random_folder_name, &*current_dir_str); // Create a new folder
-                            println!("Created folder: {}", random_folder_name);
+                            new_item_dialog.open(NewItemKind::Folder);  // Ask for a name
                             ui.close_menu(); // Close the context menu
                         }
+                        if ui.add(egui::Button::new("Select All").shortcut_text("Ctrl+A")).clicked() {
+                            selection.select_all(listing.entries());
+                            ui.close_menu();
+                        }
+                        let bookmarked = settings.bookmarks.contains(navigation.current());
+                        if ui.add_enabled(!bookmarked, egui::Button::new("Bookmark This Folder").shortcut_text("Ctrl+D")).clicked() {
+                            if let Err(e) = settings.bookmarks.add(navigation.current()) {
+                                notices_writer.send(Notice::error(e));
+                            }
+                            ui.close_menu();
+                        }
                     });
 
-                    // Show the file creation popup
-                    if *show_file_popup {
-                        egui::Window::new("Create a New File")
-                            .resizable(false)
-                            .show(ctx, |ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label("Enter content for new file...");
-                                });
-
-                                // Text editor for entering file content
-                                ui.add_sized(
-                                    egui::vec2(ui.available_width(), ui.available_height() - 40.0),
-                                    egui::TextEdit::multiline(&mut *input_text).desired_rows(10),
-                                );
-
-                                // Buttons to save or cancel the file creation
-                                ui.horizontal(|ui| {
-                                    if ui.button("Save").clicked() {
-                                        let random_file_name = format!("file_{}.txt", generate_random_number());  // Generate a random file name
-                                        create_file(&random_file_name, &*current_dir_str, &*input_text);  // Create the file and save content
-                                        *show_file_popup = false;  // Close the file popup
-                                    }
-                                    if ui.button("Cancel").clicked() {
-                                        *show_file_popup = false;  // Close the file popup
-                                    }
-                                });
-                            });
-                    }
-
-                    // Show the folder creation popup
-                    if *show_folder_popup {
-                        let random_folder_name = format!("folder_{}", generate_random_number());  // Generate a random folder name
-                        create_folder(&random_folder_name, &*current_dir_str);  // Create the folder
-                        *show_folder_popup = false;  // Close the folder popup
+                    // Show the name dialog for a new file or folder
+                    let current_dir = navigation.current();
+                    match new_item_dialog.show(ctx, &*storage.0, &listing, &templates) {
+                        Some(NewItem::File { name, content }) => {
+                            match history.0.create_file(&*storage.0, current_dir, &name, &content) {  // Create the file with the template's content
+                                Ok(file_path) => {
+                                    info!("File created: {:?}", file_path);
+                                    changes.push(FsChange::Created(file_path.clone()));
+                                    // Open the new file in the editor to fill it in
+                                    *editor = Editor { text: content, file: Some(file_path), open: true };
+                                }
+                                Err(e) => notices.push(Notice::error(format!("Couldn't create file: {}", e))),
+                            }
+                        }
+                        Some(NewItem::Folder { name }) => {
+                            match history.0.create_folder(&*storage.0, current_dir, &name) {  // Create the folder
+                                Ok(folder_path) => {
+                                    info!("Folder created: {:?}", folder_path);
+                                    changes.push(FsChange::Created(folder_path));
+                                }
+                                Err(e) => notices.push(Notice::error(format!("Couldn't create folder: {}", e))),
+                            }
+                        }
+                        None => {}
                     }
                     //####
-                    // Display files and folders in the current directory
+                    // Display files and folders in the current directory, or two folders side by side
                     ui.vertical(|ui| {
-                        // Use a group styled with a frame for the outlined container
-                        egui::Frame::none()
-                            .stroke(egui::Stroke::new(1.0, egui::Color32::BLACK)) // Black outline
-                            .fill(egui::Color32::WHITE) // White background
-                            .inner_margin(egui::vec2(10.0, 10.0)) // Inner padding
-                            .rounding(egui::Rounding::same(10.0)) // Rounded corners
-                            .show(ui, |ui| {
-                                // Add the ScrollArea for the files and folders list
-                                egui::ScrollArea::vertical() // Makes the container scrollable vertically
-                                    .auto_shrink([false, true]) // Only shrink horizontally; keep the vertical scrolling
-                                    .show(ui, |ui| {
-                                        const COLUMNS: usize = 6; // Number of columns in the grid
-                                        let mut current_col = 0; // Track the current column
-
-                                        // Create a horizontal wrapped layout for the grid
-                                        ui.horizontal_wrapped(|ui| {
-                                            for item in files_and_folders.iter() {
-                                                let item_name = item.file_name().unwrap_or_default().to_string_lossy();
-
-                                                // Handle directory or file item
-                                                ui.vertical(|ui| {
-                                                    if item.is_dir() {
-                                                        let logo = ui.add(
-                                                            egui::ImageButton::new(
-                                                                egui::Image::new(egui::include_image!("assets/folder.png"))
-                                                                    .fit_to_exact_size(egui::vec2(75.0, 75.0)),
-                                                            )
-                                                            .frame(false),
-                                                        );
-
-                                                        if logo.clicked() {
-                                                            *current_dir_str = format!("{}/{}", *current_dir_str, item_name);
-                                                        }
-
-                                                                                        // Handle right-click on the folder
-                                                    if logo.secondary_clicked() {
-                                                        println!("Right-clicked on folder: {}", item_name);
-                                                    }
-
-                                                    // Folder context menu with delete option
-                                                    logo.context_menu(|ui| {
-                                                        if ui.button("Delete Folder").clicked() {
-                                                            delete_folder(item);  // Delete the folder
-                                                        }
-                                                    });
-
-                                                        ui.label(item_name);
-                                                    } else {
-                                                        let logo = ui.add(
-                                                            egui::ImageButton::new(
-                                                                egui::Image::new(egui::include_image!("assets/file.png"))
-                                                                    .fit_to_exact_size(egui::vec2(75.0, 75.0)),
-                                                            )
-                                                            .frame(false),
-                                                        );
-
-                                                        if logo.clicked() {
-                                                            open_file_content(item, &mut *input_text, &mut *loaded_file);
-                                                            *show_save_popup = true;
-                                                        }
-
-                                                    // File context menu with delete option
-                                                    logo.context_menu(|ui| {
-                                                        if ui.button("Delete File").clicked() {
-                                                            delete_file(item);  // Delete the file
-                                                        }
-                                                    });
-
-                                                        ui.label(item_name);
-                                                    }
-                                                });
-
-                                                // Move to the next column, reset to first column if reached COLUMNS limit
-                                                current_col += 1;
-                                                if current_col >= COLUMNS {
-                                                    current_col = 0;
-                                                    ui.end_row(); // Start a new row after every 'COLUMNS' items
-                                                }
-                                            }
-                                        });
-                                    });
+                        if !commander.is_open() {
+                            grid_actions = grid::item_grid(ui, &*storage.0, &listing, view, icon_size, &mut selection, &mut inline_rename, &clipboard, &settings.bookmarks, &mut thumbnails);
+                            return;
+                        }
+                        let other_on_left = commander.other_on_left();
+                        let other = commander.other_pane(&navigation);
+                        other.listing.navigate(other.navigation.current());
+                        let other_view = settings.folder_views.get(other.navigation.current());
+                        other.listing.arrange(other_view.sort, other_view.grouping);
+                        other.selection.retain_listed(&other.listing);
+                        ui.columns(2, |columns| {
+                            let [left, right] = columns else {
+                                return;
+                            };
+                            let (active_ui, other_ui) = if other_on_left { (right, left) } else { (left, right) };
+                            // Each side keeps its widget IDs when the panes swap, so a double click can finish
+                            active_ui.push_id(!other_on_left, |ui| {
+                                commander::pane_header(ui, navigation.current(), true);
+                                grid_actions = grid::item_grid(ui, &*storage.0, &listing, view, icon_size, &mut selection, &mut inline_rename, &clipboard, &settings.bookmarks, &mut thumbnails);
+                            });
+                            other_ui.push_id(other_on_left, |ui| {
+                                let header = commander::pane_header(ui, other.navigation.current(), false);
+                                grid_actions.extend(grid::item_grid(ui, &*storage.0, &other.listing, other_view, icon_size, &mut other.selection, &mut inline_rename, &clipboard, &settings.bookmarks, &mut thumbnails));
+                                // Clicking anywhere in the other pane makes it the active one
+                                switch_panes = header.clicked() || (ui.ui_contains_pointer() && ui.input(|i| i.pointer.any_click()));
                             });
+                        });
                     });
                    //###
                     
@@ -256,110 +282,120 @@ fn ui_system(
                     ui.add_space(top_half_height);
 
                     // Show the save popup if a file is loaded
-                    if *show_save_popup {
+                    if editor.open {
                         egui::Window::new("Save/Cancel")
                             .resizable(false)
                             .show(ctx, |ui| {
                                 ui.horizontal(|ui| {
                                     if ui.button("Save").clicked() {
-                                        if let Some(ref file_path) = *loaded_file {
-                                            save_content(&*input_text, file_path.as_path());  // Save content to the file
+                                        let mut saved = true;
+                                        if let Some(ref file_path) = editor.file {
+                                            match history.0.save(&*storage.0, file_path, &editor.text) {  // Save content to the file
+                                                Ok(()) => {
+                                                    info!("Content saved to {:?}", file_path);
+                                                    changes.push(FsChange::Modified(file_path.clone()));
+                                                }
+                                                Err(e) => {
+                                                    notices.push(Notice::error(format!("Couldn't save file: {}", e)));
+                                                    saved = false;  // Stay open, so what was typed isn't lost and Save can be tried again
+                                                }
+                                            }
+                                        }
+                                        if saved {
+                                            editor.open = false;  // Close the save popup
                                         }
-                                        *show_save_popup = false;  // Close the save popup
                                     }
                                     if ui.button("Cancel").clicked() {
-                                        *input_text = String::new();  // Clear input text
-                                        *loaded_file = None;  // Reset loaded file
-                                        *show_save_popup = false;  // Close the save popup
+                                        *editor = Editor::default();  // Clear the text and forget the file
                                     }
                                 });
 
                                 // Text editor to modify file content
                                 ui.add_sized(
                                     egui::vec2(ui.available_width(), ui.available_height() - 40.0),
-                                    egui::TextEdit::multiline(&mut *input_text).desired_rows(10),
+                                    egui::TextEdit::multiline(&mut editor.text).desired_rows(10),
                                 );
                             });
                     }
                 });
         });
-}
-
-// Generates a random number to append to file/folder names
-fn generate_random_number() -> u32 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(10000..99999)
-}
 
-// Creates a new file with the specified name, writes content to it
-fn create_file(file_name: &str, current_dir_str: &str, content: &str) {
-    let full_path = Path::new(current_dir_str).join(file_name);  // Full file path
-    if let Err(e) = File::create(&full_path) {  // Create the file
-        eprintln!("Error creating file: {}", e);
-    } else {
-        println!("File created: {:?}", full_path);
-        save_content(content, &full_path);  // Save the provided content to the file
-    }
-}
+    dnd::drag_preview(ctx);  // Name or count of the items being dragged
 
-// Creates a new folder with the specified name
-fn create_folder(folder_name: &str, current_dir_str: &str) {
-    let folder_path = Path::new(current_dir_str).join(folder_name);  // Full folder path
-    if let Err(e) = fs::create_dir(&folder_path) {  // Create the folder
-        eprintln!("Error creating folder: {}", e);
-    } else {
-        println!("Folder created: {:?}", folder_path);
+    // Anything done in the other pane is done in it as the active one
+    if switch_panes {
+        commander.swap(&mut navigation, &mut selection, &mut listing);
     }
-}
-
-// Saves the input text to the specified file path
-fn save_content(input_text: &str, file_path: &Path) {
-    let mut file = match File::create(file_path) {  // Open the file for writing
-        Ok(file) => file,
-        Err(e) => {
-            eprintln!("Error opening file for writing: {}", e);
-            return;
+    for action in grid_actions {
+        match action {
+            GridAction::OpenFolder(dir) => {
+                navigation.open(&dir);
+                selection.clear();
+                sounds.send(PlaySound(SoundEvent::OpenFolder));
+            }
+            GridAction::OpenFile(file) => match ops::open_file_content(&*storage.0, &file) {
+                Ok(content) => {
+                    *editor = Editor { text: content, file: Some(file), open: true };  // Load the content into the editor
+                    sounds.send(PlaySound(SoundEvent::OpenFile));
+                }
+                Err(e) => notices.push(Notice::error(format!("Couldn't open file: {}", e))),  // The app keeps running
+            },
+            GridAction::OpenInNewTab(dir) => {
+                tab_actions.send(TabAction::OpenInBackground(dir));
+            }
+            GridAction::Copy(items) => clipboard.copy(items),
+            GridAction::Cut(items) => clipboard.cut(items),
+            GridAction::PasteInto(dir) => {
+                transfer_requests.send(clipboard.paste(&dir));
+            }
+            GridAction::Bookmark(dir) => {
+                if let Err(e) = settings.bookmarks.add(&dir) {
+                    notices_writer.send(Notice::error(e));
+                }
+            }
+            GridAction::Trash(targets) => {
+                sounds.send(PlaySound(SoundEvent::Delete));  // Once for the whole selection
+                for target in targets {
+                    match history.0.delete(&*storage.0, &target) {  // Moves it to the trash, so it can be undone or restored
+                        Ok(_) => {
+                            info!("Moved to trash: {:?}", target);
+                            changes.push(FsChange::Removed(target));
+                        }
+                        Err(e) => notices.push(Notice::error(format!("Couldn't move to trash: {}", e))),
+                    }
+                }
+            }
+            GridAction::Compress(targets) => {
+                compress_requests.send(CompressRequest(targets));
+            }
+            GridAction::Transfer(request) => {
+                transfer_requests.send(request);
+            }
+            GridAction::Refused(refusal) => notices.push(Notice::error(refusal)),
+            GridAction::Rename { from, to } => renames.push((from, to)),
+            GridAction::Zoom(size) => {
+                let size = IconSize::new(size);
+                if settings.icon_size != size {
+                    settings.icon_size = size;  // Only when it changed, so the settings aren't saved every frame
+                }
+            }
+            GridAction::Sort { dir, sort } => {
+                let view = FolderView { sort, ..settings.folder_views.get(&dir) };
+                view::remember(&mut settings, &dir, view);
+            }
         }
-    };
-
-    if let Err(e) = file.write_all(input_text.as_bytes()) {  // Write content to file
-        eprintln!("Error writing to file: {}", e);
-    } else {
-        println!("Content saved to {:?}", file_path);
     }
-}
-
-// Opens the content of a file and loads it into the input_text editor
-fn open_file_content(file_path: &Path, input_text: &mut String, loaded_file: &mut Option<PathBuf>) {
-    let mut file = File::open(file_path).unwrap_or_else(|_| {  // Open the file for reading
-        eprintln!("Error opening file: {:?}", file_path);
-        std::process::exit(1);
-    });
 
-    let mut content = String::new();  // String to hold the file content
-    if let Err(e) = file.read_to_string(&mut content) {  // Read the file content
-        eprintln!("Error reading file: {:?}", e);
-    } else {
-        *input_text = content;  // Load the content into input_text
-        *loaded_file = Some(file_path.to_path_buf());  // Store the loaded file path
-        println!("Loaded content from {:?}", file_path);
-    }
-}
- 
-// Deletes a file
-fn delete_file(file_path: &Path) {
-    if let Err(e) = fs::remove_file(file_path) {  // Delete the file
-        eprintln!("Error deleting file: {}", e);
-    } else {
-        println!("File deleted: {:?}", file_path);
+    for (from, to) in renames {
+        match history.0.rename(&*storage.0, &from, &to) {  // Recorded, so Ctrl+Z puts the old name back
+            Ok(()) => {
+                info!("Renamed {:?} to {:?}", from, to);
+                changes.push(FsChange::Renamed { from, to });
+            }
+            Err(e) => notices.push(Notice::error(format!("Couldn't rename: {}", e))),
+        }
     }
-}
 
-// Deletes a folder
-fn delete_folder(folder_path: &Path) {
-    if let Err(e) = fs::remove_dir_all(folder_path) {  // Delete the folder
-        eprintln!("Error deleting folder: {}", e);
-    } else {
-        println!("Folder deleted: {:?}", folder_path);
-    }
+    fs_changes.send_batch(changes);
+    notices_writer.send_batch(notices);
 }
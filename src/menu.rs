@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::commander::Commander;
+use crate::folder_tree::FolderTree;
+use crate::navigation::Navigation;
+use crate::history::{History, HistoryAction, HistoryView};
+use crate::notifications::Notifications;
+use crate::settings::Settings;
+use crate::sounds::SoundSettingsView;
+use crate::view::{self, Grouping, IconSize, ViewMode};
+
+// The menu bar along the top of the window, above the path bar.
+// Has to run before `ui_system` so its panel ends up outermost.
+#[allow(clippy::too_many_arguments)]
+pub fn menu_bar(
+    mut contexts: EguiContexts,
+    history: Res<History>,
+    mut history_view: ResMut<HistoryView>,
+    mut history_actions: EventWriter<HistoryAction>,
+    mut notifications: ResMut<Notifications>,
+    mut settings: ResMut<Settings>,
+    mut sound_settings: ResMut<SoundSettingsView>,
+    mut folder_tree: ResMut<FolderTree>,
+    mut commander: ResMut<Commander>,
+    navigation: Res<Navigation>,
+) {
+    egui::TopBottomPanel::top("menu_bar").show(contexts.ctx_mut(), |ui| {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("Edit", |ui| {
+                let undo = egui::Button::new("Undo").shortcut_text("Ctrl+Z");
+                if ui.add_enabled(history.0.can_undo(), undo).clicked() {
+                    history_actions.send(HistoryAction::Undo);
+                    ui.close_menu();
+                }
+                let redo = egui::Button::new("Redo").shortcut_text("Ctrl+Shift+Z");
+                if ui.add_enabled(history.0.can_redo(), redo).clicked() {
+                    history_actions.send(HistoryAction::Redo);
+                    ui.close_menu();
+                }
+                ui.separator();
+                if ui.button("History").clicked() {
+                    history_view.open = true;
+                    ui.close_menu();
+                }
+            });
+            ui.menu_button("View", |ui| {
+                // How the folder being shown is laid out, remembered for that folder
+                let dir = navigation.current();
+                let mut folder_view = settings.folder_views.get(dir);
+                ui.radio_value(&mut folder_view.mode, ViewMode::Icons, "Icons").on_hover_text("Ctrl+1");
+                ui.radio_value(&mut folder_view.mode, ViewMode::Details, "Details").on_hover_text("Ctrl+2");
+                ui.menu_button("Sort By", |ui| {
+                    for (key, title) in view::SORT_KEYS {
+                        ui.radio_value(&mut folder_view.sort.key, key, title);
+                    }
+                    ui.separator();
+                    ui.radio_value(&mut folder_view.sort.descending, false, "Ascending");
+                    ui.radio_value(&mut folder_view.sort.descending, true, "Descending");
+                });
+                ui.menu_button("Group By", |ui| {
+                    ui.radio_value(&mut folder_view.grouping, Grouping::None, "Nothing");
+                    ui.radio_value(&mut folder_view.grouping, Grouping::Type, "Type");
+                    ui.radio_value(&mut folder_view.grouping, Grouping::Date, "Date Modified");
+                });
+                view::remember(&mut settings, dir, folder_view);
+                let mut icon_size = settings.icon_size.get();  // Only touched when dragged, so it isn't saved every frame
+                if ui.add(egui::Slider::new(&mut icon_size, IconSize::RANGE).text("Icon Size")).on_hover_text("Ctrl+scroll over the icons").changed() {
+                    settings.icon_size = IconSize::new(icon_size);
+                }
+                ui.separator();
+                ui.checkbox(&mut folder_tree.open, "Folder Tree");
+                let mut dual_pane = commander.is_open();
+                if ui.checkbox(&mut dual_pane, "Dual Pane").on_hover_text("F5 copies the selection to the other pane, F6 moves it").changed() {
+                    commander.set_open(dual_pane);
+                }
+                if ui.button(format!("Error Log ({})", notifications.error_count())).clicked() {
+                    notifications.show_log = true;
+                    ui.close_menu();
+                }
+            });
+            ui.menu_button("Settings", |ui| {
+                let mut muted = settings.sounds.muted;  // Only touched when toggled, so it isn't saved every frame
+                if ui.checkbox(&mut muted, "Mute Sounds").changed() {
+                    settings.sounds.muted = muted;
+                }
+                if ui.button("Sounds…").clicked() {
+                    sound_settings.open = true;
+                    ui.close_menu();
+                }
+            });
+        });
+    });
+}
@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::paths;
+use std::path::{Path, PathBuf};
+
+use crate::jail::JailRoot;
+use crate::selection::Selection;
+
+// Which folder is shown, with Back/Forward history like a web browser.
+// Alt+Left/Right, the mouse's back/forward buttons, Alt+Up for the parent and Alt+Home.
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Navigation>().add_systems(Update, navigation_shortcuts);
+    }
+}
+
+// Where the app starts, and Home when $HOME isn't set. In a jail its root is both.
+const START_DIR: &str = "./root";
+
+// Folders are kept absolute and without `.` or `..`, as the OS spells them (not
+// necessarily UTF-8), so the same folder always compares equal to itself.
+#[derive(Resource)]
+pub struct Navigation {
+    current: PathBuf,  // The folder being shown
+    back: Vec<PathBuf>,  // Earlier folders, most recent last
+    forward: Vec<PathBuf>,  // Folders left with Back, most recent last
+    jail: Option<PathBuf>,  // Nothing above this can be opened
+    workspace: PathBuf,  // Where the app started, the top of the folder tree
+}
+
+impl FromWorld for Navigation {
+    fn from_world(world: &mut World) -> Self {
+        let jail = world.resource::<JailRoot>().0.clone();
+        let workspace = jail.clone().unwrap_or_else(|| normalize(Path::new(START_DIR)));
+        Self { current: workspace.clone(), back: Vec::new(), forward: Vec::new(), jail, workspace }
+    }
+}
+
+impl Navigation {
+    pub fn current(&self) -> &Path {
+        &self.current
+    }
+
+    // Navigation for a new tab: the same limits, starting at `dir` without any history
+    pub fn fresh(&self, dir: &Path) -> Navigation {
+        let mut fresh = Navigation {
+            current: self.current.clone(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            jail: self.jail.clone(),
+            workspace: self.workspace.clone(),
+        };
+        fresh.open(dir);
+        fresh.back.clear();
+        fresh
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    // Goes to `dir`, remembering where we were for Back. Returns whether anything changed.
+    pub fn open(&mut self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        if dir == self.current || !self.is_allowed(&dir) {
+            return false;
+        }
+        self.back.push(std::mem::replace(&mut self.current, dir));
+        self.forward.clear();  // A new branch, like following a link after going back
+        true
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    pub fn can_go_up(&self) -> bool {
+        self.parent().is_some()
+    }
+
+    pub fn back(&mut self) -> bool {
+        let Some(previous) = self.back.pop() else {
+            return false;
+        };
+        self.forward.push(std::mem::replace(&mut self.current, previous));
+        true
+    }
+
+    pub fn forward(&mut self) -> bool {
+        let Some(next) = self.forward.pop() else {
+            return false;
+        };
+        self.back.push(std::mem::replace(&mut self.current, next));
+        true
+    }
+
+    // Goes to the folder containing the current one
+    pub fn up(&mut self) -> bool {
+        match self.parent() {
+            Some(parent) => self.open(&parent),
+            None => false,  // Already at the root
+        }
+    }
+
+    // Goes to the user's home folder, or the jail's root
+    pub fn home(&mut self) -> bool {
+        let home = self.jail.clone().or_else(|| std::env::var_os("HOME").map(PathBuf::from));
+        self.open(&home.unwrap_or_else(|| PathBuf::from(START_DIR)))
+    }
+
+    fn parent(&self) -> Option<PathBuf> {
+        self.current.parent().filter(|parent| self.is_allowed(parent)).map(Path::to_path_buf)
+    }
+
+    // Only by the path, so this is just about what's offered; `Storage` refuses links out of the jail
+    fn is_allowed(&self, dir: &Path) -> bool {
+        self.jail.as_ref().is_none_or(|root| dir.starts_with(root))
+    }
+}
+
+// Falls back to the path as given if the working directory is gone
+fn normalize(dir: &Path) -> PathBuf {
+    paths::normalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+// Alt+arrows, Alt+Home and the mouse's side buttons. Text fields keep Alt+arrows for themselves.
+fn navigation_shortcuts(
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut navigation: ResMut<Navigation>,
+    mut selection: ResMut<Selection>,
+) {
+    let ctx = contexts.ctx_mut();
+    let (mut back, mut forward, mut up, mut home) = (false, false, false, false);
+    if !ctx.wants_keyboard_input() {
+        ctx.input_mut(|i| {
+            back = i.consume_key(egui::Modifiers::ALT, egui::Key::ArrowLeft);
+            forward = i.consume_key(egui::Modifiers::ALT, egui::Key::ArrowRight);
+            up = i.consume_key(egui::Modifiers::ALT, egui::Key::ArrowUp);
+            home = i.consume_key(egui::Modifiers::ALT, egui::Key::Home);
+        });
+    }
+    // egui doesn't get the side buttons from bevy_egui, so they're read from Bevy directly
+    back |= mouse.just_pressed(MouseButton::Back);
+    forward |= mouse.just_pressed(MouseButton::Forward);
+
+    // Only touch the resource when something happens, so change detection stays meaningful
+    let moved = (back && navigation.back())
+        | (forward && navigation.forward())
+        | (up && navigation.up())
+        | (home && navigation.home());
+    if moved {
+        selection.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(dir: &str, jail: Option<&str>) -> Navigation {
+        let dir = PathBuf::from(dir);
+        Navigation { current: dir.clone(), back: Vec::new(), forward: Vec::new(), jail: jail.map(PathBuf::from), workspace: dir }
+    }
+
+    #[test]
+    fn back_and_forward_retrace_the_steps() {
+        let mut navigation = at("/a", None);
+        assert!(navigation.open(Path::new("/a/b")));
+        assert!(navigation.open(Path::new("/a/b/c")));
+
+        assert!(navigation.back());
+        assert!(navigation.back());
+        assert_eq!(navigation.current(), Path::new("/a"));
+        assert!(!navigation.back());
+
+        assert!(navigation.forward());
+        assert_eq!(navigation.current(), Path::new("/a/b"));
+        assert!(navigation.forward());
+        assert_eq!(navigation.current(), Path::new("/a/b/c"));
+        assert!(!navigation.can_go_forward());
+    }
+
+    #[test]
+    fn opening_a_folder_after_going_back_drops_the_forward_steps() {
+        let mut navigation = at("/a", None);
+        navigation.open(Path::new("/a/b"));
+        navigation.open(Path::new("/a/b/c"));
+        navigation.back();
+        navigation.back();
+        assert!(navigation.can_go_forward());
+
+        assert!(navigation.open(Path::new("/x")));
+        assert!(!navigation.can_go_forward());
+        assert!(!navigation.forward());
+        assert!(navigation.back());
+        assert_eq!(navigation.current(), Path::new("/a"));
+    }
+
+    #[test]
+    fn opening_the_same_folder_again_changes_nothing() {
+        let mut navigation = at("/a", None);
+        navigation.open(Path::new("/a/b"));
+        navigation.back();
+        assert!(!navigation.open(Path::new("/a/./")));
+        assert!(navigation.can_go_forward());  // So it isn't lost either
+    }
+
+    #[test]
+    fn nothing_above_the_jail_is_opened() {
+        let mut navigation = at("/jail/sub", Some("/jail"));
+        assert!(navigation.up());
+        assert_eq!(navigation.current(), Path::new("/jail"));
+        assert!(!navigation.can_go_up());
+        assert!(!navigation.open(Path::new("/elsewhere")));
+        assert!(!navigation.open(Path::new("/jail/..")));
+        assert_eq!(navigation.current(), Path::new("/jail"));
+    }
+
+    #[test]
+    fn a_fresh_navigation_has_no_history() {
+        let mut navigation = at("/a", None);
+        navigation.open(Path::new("/a/b"));
+        let fresh = navigation.fresh(Path::new("/c"));
+        assert_eq!(fresh.current(), Path::new("/c"));
+        assert!(!fresh.can_go_back() && !fresh.can_go_forward());
+    }
+}
@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use file_manager_core::{names, templates, FileSystem, FsError, LocalFs, NameError, Template};
+use std::path::{Path, PathBuf};
+
+use crate::listing::DirectoryListing;
+use crate::notifications::Notice;
+
+// Where file templates are read from, next to the app like `./root`
+const TEMPLATES_DIR: &str = "./templates";
+// Offered in the extension menu on top of the ones the templates use
+const COMMON_EXTENSIONS: &[&str] = &["txt", "md", "json", "rs", "toml", "html", "css", "js", "py"];
+
+// Loads the file templates the "Create File" dialog offers
+pub struct NewItemPlugin;
+
+impl Plugin for NewItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_templates);
+    }
+}
+
+// Templates offered when creating a file, the empty one first
+#[derive(Resource)]
+pub struct Templates(pub Vec<Template>);
+
+// What the dialog asked to create, once the name checks out
+pub enum NewItem {
+    File { name: String, content: String },
+    Folder { name: String },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NewItemKind {
+    File,
+    Folder,
+}
+
+// The "Create File" / "Create Folder" dialog: a name, and for files an extension and a template
+#[derive(Default)]
+pub struct NewItemDialog {
+    kind: Option<NewItemKind>,  // None while closed
+    name: String,
+    extension: String,  // Without the dot
+    template: usize,  // Index into `Templates`
+    focus_name: bool,  // Put the cursor in the name field on the first frame
+    checked: Option<Checked>,  // The last name checked, so the disk is only asked again when it or the folder changes
+}
+
+struct Checked {
+    revision: u64,  // Of the listing at the time; something appearing or going may take or free the name
+    name: String,
+    result: Result<PathBuf, NameError>,
+}
+
+impl NewItemDialog {
+    pub fn open(&mut self, kind: NewItemKind) {
+        *self = Self {
+            kind: Some(kind),
+            name: String::new(),
+            extension: Template::empty().extension,
+            template: 0,
+            focus_name: true,
+            checked: None,
+        };
+    }
+
+    // The name the item will get, with the extension for files
+    fn full_name(&self) -> String {
+        let name = self.name.trim();
+        let extension = self.extension.trim().trim_start_matches('.');
+        if self.kind == Some(NewItemKind::File) && !extension.is_empty() {
+            format!("{}.{}", name, extension)
+        } else {
+            name.to_string()
+        }
+    }
+
+    // Draws the dialog while it's open; returns what to create when the user confirms a valid name
+    pub fn show(&mut self, ctx: &egui::Context, fs: &dyn FileSystem, listing: &DirectoryListing, templates: &Templates) -> Option<NewItem> {
+        let kind = self.kind?;
+        let (dir, revision) = (listing.dir(), listing.revision());
+        let full_name = self.full_name();
+        // Checked whenever the name changes, so the error shows while typing
+        if !self.checked.as_ref().is_some_and(|checked| checked.revision == revision && checked.name == full_name) {
+            let result = names::check_new_name(fs, dir, &full_name);
+            self.checked = Some(Checked { revision, name: full_name.clone(), result });
+        }
+        let problem = self.checked.as_ref().and_then(|checked| checked.result.as_ref().err()).map(NameError::to_string);
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+
+        let title = match kind {
+            NewItemKind::File => "Create File",
+            NewItemKind::Folder => "Create Folder",
+        };
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Grid::new("new_item_fields").num_columns(2).show(ui, |ui| {
+                    ui.label("Name:");
+                    let name_field = ui.add(egui::TextEdit::singleline(&mut self.name).desired_width(200.0));
+                    if std::mem::take(&mut self.focus_name) {
+                        name_field.request_focus();
+                    }
+                    // Enter in the name field works like the Create button
+                    if name_field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        confirmed = true;
+                    }
+                    ui.end_row();
+
+                    if kind == NewItemKind::File {
+                        ui.label("Extension:");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.extension).desired_width(60.0));
+                            ui.menu_button("▾", |ui| {
+                                for extension in extension_choices(templates) {
+                                    let label = if extension.is_empty() { "(none)" } else { extension.as_str() };
+                                    if ui.button(label).clicked() {
+                                        self.extension = extension;
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        });
+                        ui.end_row();
+
+                        ui.label("Template:");
+                        let selected = templates.0.get(self.template).map_or("Empty", |template| template.name.as_str());
+                        egui::ComboBox::from_id_salt("new_file_template")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (index, template) in templates.0.iter().enumerate() {
+                                    if ui.selectable_label(index == self.template, &template.name).clicked() {
+                                        self.template = index;
+                                        // Picking a template suggests its extension
+                                        if !template.extension.is_empty() {
+                                            self.extension = template.extension.clone();
+                                        }
+                                    }
+                                }
+                            });
+                        ui.end_row();
+                    }
+                });
+
+                match &problem {
+                    None => ui.weak(format!("Creates \"{}\"", full_name)),
+                    Some(problem) => ui.colored_label(ui.visuals().error_fg_color, problem),
+                };
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(problem.is_none(), egui::Button::new("Create")).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if !open || cancelled {
+            self.kind = None;
+            return None;
+        }
+        if !confirmed || problem.is_some() {
+            return None;
+        }
+        // Something with that name may have appeared since it was last checked
+        if let Err(e) = names::check_new_name(fs, dir, &full_name) {
+            self.checked = Some(Checked { revision, name: full_name, result: Err(e) });
+            return None;
+        }
+
+        self.kind = None;
+        Some(match kind {
+            NewItemKind::File => {
+                let content = templates.0.get(self.template).map(|template| template.render(&full_name)).unwrap_or_default();
+                NewItem::File { name: full_name, content }
+            }
+            NewItemKind::Folder => NewItem::Folder { name: full_name },
+        })
+    }
+}
+
+// The extensions in the dropdown: the templates' own, then common ones, then no extension
+fn extension_choices(templates: &Templates) -> Vec<String> {
+    let mut choices: Vec<String> = Vec::new();
+    let all = templates.0.iter().map(|template| template.extension.as_str()).chain(COMMON_EXTENSIONS.iter().copied());
+    for extension in all {
+        if !extension.is_empty() && !choices.iter().any(|choice| choice == extension) {
+            choices.push(extension.to_string());
+        }
+    }
+    choices.push(String::new());
+    choices
+}
+
+// Reads the templates folder once at startup; without one only the empty template is offered
+// They ship with the app, so they're read from the disk even when the user is jailed elsewhere
+fn load_templates(mut commands: Commands, mut notices: EventWriter<Notice>) {
+    let templates = match templates::load_templates(&LocalFs, Path::new(TEMPLATES_DIR)) {
+        Ok(templates) => templates,
+        Err(FsError::NotFound(_)) => vec![Template::empty()],
+        Err(e) => {
+            notices.send(Notice::error(format!("Couldn't load file templates: {}", e)));
+            vec![Template::empty()]
+        }
+    };
+    commands.insert_resource(Templates(templates));
+}
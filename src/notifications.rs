@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use chrono::{Local, NaiveDateTime};
+
+// How long a toast stays up before fading away, in seconds
+const INFO_TOAST_SECS: f64 = 4.0;
+const ERROR_TOAST_SECS: f64 = 8.0;
+const MAX_TOASTS: usize = 5;
+
+// Shows failures (and the odd confirmation) as toasts in the corner of the window,
+// and keeps every error in a log that can be opened from View > Error Log.
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Notifications>()
+            .add_event::<Notice>()
+            .add_systems(Update, (collect_notices, draw_toasts, error_log_window).chain());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+// Something the user should hear about. Any system can send one.
+#[derive(Event, Debug, Clone)]
+pub struct Notice {
+    pub level: Level,
+    pub text: String,
+}
+
+impl Notice {
+    pub fn info(text: impl ToString) -> Self {
+        Self { level: Level::Info, text: text.to_string() }
+    }
+
+    pub fn error(text: impl ToString) -> Self {
+        Self { level: Level::Error, text: text.to_string() }
+    }
+}
+
+struct Toast {
+    notice: Notice,
+    expires_at: f64,  // In `Time::elapsed_secs_f64`
+}
+
+#[derive(Resource, Default)]
+pub struct Notifications {
+    toasts: Vec<Toast>,  // Oldest first
+    log: Vec<(NaiveDateTime, String)>,  // Every error since startup
+    pub show_log: bool,
+}
+
+impl Notifications {
+    pub fn error_count(&self) -> usize {
+        self.log.len()
+    }
+}
+
+// Turns incoming notices into toasts, and errors into log entries
+fn collect_notices(time: Res<Time>, mut notices: EventReader<Notice>, mut notifications: ResMut<Notifications>) {
+    let now = time.elapsed_secs_f64();
+    for notice in notices.read() {
+        let lifetime = match notice.level {
+            Level::Info => INFO_TOAST_SECS,
+            Level::Error => {
+                error!("{}", notice.text);  // Still useful when running from a terminal
+                notifications.log.push((Local::now().naive_local(), notice.text.clone()));
+                ERROR_TOAST_SECS
+            }
+        };
+        notifications.toasts.push(Toast { notice: notice.clone(), expires_at: now + lifetime });
+    }
+
+    notifications.toasts.retain(|toast| toast.expires_at > now);
+    let overflow = notifications.toasts.len().saturating_sub(MAX_TOASTS);
+    notifications.toasts.drain(..overflow);
+}
+
+// Stacks the live toasts in the bottom-right corner, newest at the bottom
+fn draw_toasts(mut contexts: EguiContexts, mut notifications: ResMut<Notifications>) {
+    if notifications.toasts.is_empty() {
+        return;
+    }
+
+    let mut dismissed = None;
+    let mut open_log = false;
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .order(egui::Order::Foreground)
+        .show(contexts.ctx_mut(), |ui| {
+            for (index, toast) in notifications.toasts.iter().enumerate() {
+                let (fill, icon) = match toast.notice.level {
+                    Level::Info => (egui::Color32::from_rgb(230, 240, 230), "ℹ"),
+                    Level::Error => (egui::Color32::from_rgb(250, 225, 220), "⚠"),
+                };
+                egui::Frame::none()
+                    .fill(fill)
+                    .stroke(egui::Stroke::new(1.0, egui::Color32::BLACK))
+                    .rounding(egui::Rounding::same(8.0))
+                    .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+                    .show(ui, |ui| {
+                        ui.set_max_width(320.0);
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(icon).color(egui::Color32::BLACK));
+                            ui.label(egui::RichText::new(&toast.notice.text).color(egui::Color32::BLACK));
+                            if toast.notice.level == Level::Error && ui.small_button("Log").clicked() {
+                                open_log = true;
+                            }
+                            if ui.small_button("✖").clicked() {
+                                dismissed = Some(index);
+                            }
+                        });
+                    });
+                ui.add_space(4.0);
+            }
+        });
+
+    if let Some(index) = dismissed {
+        notifications.toasts.remove(index);
+    }
+    if open_log {
+        notifications.show_log = true;
+    }
+}
+
+// Every error since startup, newest first
+fn error_log_window(mut contexts: EguiContexts, mut notifications: ResMut<Notifications>) {
+    if !notifications.show_log {
+        return;
+    }
+
+    let mut open = true;
+    let mut clear = false;
+    egui::Window::new("Error Log")
+        .open(&mut open)
+        .default_size(egui::vec2(460.0, 260.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} error(s)", notifications.log.len()));
+                if ui.button("Clear").clicked() {
+                    clear = true;
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                for (at, text) in notifications.log.iter().rev() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(egui::RichText::new(at.format("%H:%M:%S").to_string()).weak());
+                        ui.label(text);
+                    });
+                }
+            });
+        });
+
+    if clear {
+        notifications.log.clear();
+    }
+    notifications.show_log = open;
+}
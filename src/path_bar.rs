@@ -0,0 +1,392 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::FileSystem;
+use std::path::{Component, Path, PathBuf};
+
+use crate::bookmarks;
+use crate::dnd;
+use crate::navigation::Navigation;
+use crate::notifications::Notice;
+use crate::selection::Selection;
+use crate::settings::Settings;
+use crate::transfer::TransferRequest;
+use crate::Storage;
+
+// The bar under the menu with Back/Forward/Up/Home and the path bar.
+// Runs after the menu bar and before `ui_system`, so the panels stack in that order.
+#[allow(clippy::too_many_arguments)]
+pub fn path_bar_panel(
+    storage: Res<Storage>,
+    mut contexts: EguiContexts,
+    mut navigation: ResMut<Navigation>,
+    mut selection: ResMut<Selection>,
+    mut transfer_requests: EventWriter<TransferRequest>,
+    mut notices: EventWriter<Notice>,
+    mut path_bar: Local<PathBar>,
+    mut settings: ResMut<Settings>,
+) {
+    egui::TopBottomPanel::top("top_panel")
+    .exact_height(50.0) // Set height to 50 px
+    .frame(
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgb(153, 153, 153)) // Set background color to #999999
+            .inner_margin(egui::Margin::same(10.0)),   // Add some padding
+    )
+    .show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space((50.0 - 20.0) / 10.0); 
+
+            egui::Frame::none()
+                .fill(egui::Color32::WHITE) 
+                .rounding(egui::Rounding::same(15.0))
+                .inner_margin(egui::Margin::symmetric(10.0, 5.0)) 
+                .stroke(egui::Stroke::new(1.0, egui::Color32::BLACK)) 
+                .show(ui, |ui| {
+                    ui.allocate_ui(egui::vec2(620.0, ui.available_height()), |ui| {
+                        ui.horizontal(|ui| {
+                            // Back, Forward, Up and Home, each disabled when there's nowhere to go
+                            let buttons = [
+                                ("⬅", "Back (Alt+Left)", navigation.can_go_back(), Navigation::back as fn(&mut Navigation) -> bool),
+                                ("➡", "Forward (Alt+Right)", navigation.can_go_forward(), Navigation::forward),
+                                ("⬆", "Up (Alt+Up)", navigation.can_go_up(), Navigation::up),
+                                ("🏠", "Home (Alt+Home)", true, Navigation::home),
+                            ];
+                            for (icon, hover, enabled, go) in buttons {
+                                let button = egui::Button::new(icon).frame(false);
+                                if ui.add_enabled(enabled, button).on_hover_text(hover).clicked() && go(&mut navigation) {
+                                    selection.clear();
+                                }
+                            }
+                            if let Some(dir) = bookmarks::bookmarks_menu(ui, &mut settings, navigation.current(), &mut notices) {
+                                navigation.open(&dir);
+                                selection.clear();
+                            }
+
+                            match path_bar.show(ui, &*storage.0, navigation.current()) {
+                                Some(PathBarAction::Navigate(dir)) => {
+                                    navigation.open(&dir);
+                                    selection.clear();
+                                }
+                                Some(PathBarAction::Drop(Ok(request))) => {
+                                    transfer_requests.send(request);
+                                }
+                                Some(PathBarAction::Drop(Err(refusal))) => {
+                                    notices.send(Notice::error(refusal));
+                                }
+                                None => {}
+                            }
+                        });
+                    });
+                });
+        });
+    });
+}
+
+// The path above the grid: a breadcrumb of clickable folders, which turns into a text
+// field when the empty part of it (or the current folder) is clicked. The typed path
+// only counts once Enter is pressed, and Tab completes folder names.
+#[derive(Default)]
+pub struct PathBar {
+    editing: Option<Editing>,
+}
+
+struct Editing {
+    from: PathBuf,  // The folder shown when editing started; relative paths start here
+    text: String,  // What's typed so far
+    focus: bool,  // Focus the field and select everything on the next frame
+    hint: Option<Hint>,  // Shown under the field until the text changes
+}
+
+enum Hint {
+    Error(String),  // Why Enter didn't go anywhere
+    Candidates(Vec<String>),  // Folders an ambiguous Tab could mean
+}
+
+// What the user did with the path bar this frame
+enum PathBarAction {
+    Navigate(PathBuf),
+    Drop(Result<TransferRequest, String>),  // Items dragged onto one of the breadcrumb folders
+}
+
+// How many ambiguous completions are listed before giving up with "…"
+const MAX_CANDIDATES: usize = 8;
+
+impl PathBar {
+    fn show(&mut self, ui: &mut egui::Ui, fs: &dyn FileSystem, current: &Path) -> Option<PathBarAction> {
+        if self.editing.is_some() {
+            return self.show_field(ui, fs);
+        }
+
+        let mut action = None;
+        let crumbs = breadcrumbs(current);
+        let last = crumbs.len().saturating_sub(1);
+        let mut after_root = true;  // No separator before the first folder, or right after "/"
+        for (index, (label, dir)) in crumbs.into_iter().enumerate() {
+            if !std::mem::replace(&mut after_root, label == "/") {
+                ui.weak("›");
+            }
+            let crumb = ui.add(egui::Button::new(label).frame(false));
+            if crumb.clicked() {
+                if index == last {
+                    self.start(current);  // The folder we're in: edit the path instead
+                } else {
+                    action = Some(PathBarAction::Navigate(dir.clone()));
+                }
+            }
+            if let Some(dropped) = dnd::drop_target(&crumb, &dir) {
+                action = Some(PathBarAction::Drop(dropped));
+            }
+        }
+
+        // The rest of the bar switches to typing a path
+        let rest = ui.available_rect_before_wrap();
+        let blank = ui.interact(rest, ui.id().with("path_bar_blank"), egui::Sense::click());
+        if blank.on_hover_cursor(egui::CursorIcon::Text).clicked() {
+            self.start(current);
+        }
+        action
+    }
+
+    fn start(&mut self, current: &Path) {
+        let text = current.to_string_lossy().into_owned();
+        self.editing = Some(Editing { from: current.to_path_buf(), text, focus: true, hint: None });
+    }
+
+    fn show_field(&mut self, ui: &mut egui::Ui, fs: &dyn FileSystem) -> Option<PathBarAction> {
+        let editing = self.editing.as_mut()?;
+        let id = ui.id().with("path_bar_field");
+
+        // Tab completes instead of moving focus, which `lock_focus` keeps from happening
+        let completing = ui.memory(|m| m.has_focus(id)) && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab));
+        if completing {
+            editing.hint = complete(fs, &editing.from, &mut editing.text).map(Hint::Candidates);
+        }
+
+        let mut output = egui::TextEdit::singleline(&mut editing.text)
+            .id(id)
+            .frame(false)
+            .lock_focus(true)
+            .desired_width(ui.available_width())
+            .show(ui);
+        if output.response.changed() {
+            editing.hint = None;
+        }
+        let focus = std::mem::take(&mut editing.focus);
+        if focus || completing {
+            let end = egui::text::CCursor::new(editing.text.chars().count());
+            let start = if focus { egui::text::CCursor::new(0) } else { end };
+            output.response.request_focus();
+            output.state.cursor.set_char_range(Some(egui::text::CCursorRange::two(start, end)));
+            output.state.store(ui.ctx(), id);
+        }
+
+        if let Some(hint) = &editing.hint {
+            let rect = output.response.rect;
+            egui::show_tooltip_at(ui.ctx(), ui.layer_id(), id.with("hint"), rect.left_bottom() + egui::vec2(0.0, 8.0), |ui| match hint {
+                Hint::Error(error) => {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                Hint::Candidates(names) => {
+                    for name in names.iter().take(MAX_CANDIDATES) {
+                        ui.label(name);
+                    }
+                    if names.len() > MAX_CANDIDATES {
+                        ui.weak("…");
+                    }
+                }
+            });
+        }
+
+        if !output.response.lost_focus() {
+            return None;
+        }
+        if !ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            self.editing = None;  // Escape or a click elsewhere leaves the path as it was
+            return None;
+        }
+        match check_dir(fs, &editing.from, &editing.text) {
+            Ok(dir) => {
+                self.editing = None;
+                Some(PathBarAction::Navigate(dir))
+            }
+            Err(error) => {
+                editing.hint = Some(Hint::Error(error));
+                output.response.request_focus();  // Keep typing to fix it
+                None
+            }
+        }
+    }
+}
+
+// Each folder along `path` with the path leading up to it
+fn breadcrumbs(path: &Path) -> Vec<(String, PathBuf)> {
+    let mut dir = PathBuf::new();
+    path.components()
+        .map(|component| {
+            dir.push(component);
+            let label = match component {
+                Component::RootDir => String::from("/"),
+                other => other.as_os_str().to_string_lossy().into_owned(),
+            };
+            (label, dir.clone())
+        })
+        .collect()
+}
+
+// The typed path with `~` and environment variables expanded, if it's an existing folder.
+// Relative paths are taken from `from`, the folder being shown.
+fn check_dir(fs: &dyn FileSystem, from: &Path, text: &str) -> Result<PathBuf, String> {
+    if text == from.to_string_lossy() {
+        return Ok(from.to_path_buf());  // Untouched, which keeps names that aren't UTF-8 intact
+    }
+    let dir = from.join(expand(text.trim())?);
+    match fs.metadata(&dir) {
+        Ok(entry) if entry.is_dir => Ok(dir),
+        Ok(_) => Err(format!("{} is a file, not a folder", dir.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(format!("{} doesn't exist", dir.display())),
+        Err(e) => Err(format!("Can't open {}: {}", dir.display(), e)),
+    }
+}
+
+// Replaces a leading `~` with the home folder and `$NAME` or `${NAME}` with that variable
+fn expand(text: &str) -> Result<String, String> {
+    let text = match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("${{HOME}}{}", rest),
+        _ => text.to_owned(),
+    };
+
+    let mut expanded = String::new();
+    let mut rest = text.as_str();
+    while let Some(dollar) = rest.find('$') {
+        expanded.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let (name, remaining) = match after.strip_prefix('{') {
+            Some(braced) => {
+                let close = braced.find('}').ok_or("Missing } after ${")?;
+                (&braced[..close], &braced[close + 1..])
+            }
+            None => {
+                let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            expanded.push('$');  // A lone $ is just part of the name
+        } else {
+            expanded.push_str(&std::env::var(name).map_err(|_| format!("${} is not set", name))?);
+        }
+        rest = remaining;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+// Completes the last part of `text` to the folders starting with it. Extends it as far as
+// they agree, adding a `/` when only one is left. Returns the choices when that's ambiguous.
+fn complete(fs: &dyn FileSystem, from: &Path, text: &mut String) -> Option<Vec<String>> {
+    let (parent, partial) = match text.rfind('/') {
+        Some(slash) => text.split_at(slash + 1),
+        None => ("", text.as_str()),
+    };
+    let dir = from.join(expand(parent).ok()?);
+
+    let mut names: Vec<String> = fs
+        .read_dir(&dir)
+        .ok()?
+        .into_iter()
+        .filter(|entry| entry.is_dir)
+        .map(|entry| entry.name().into_owned())
+        .filter(|name| name.starts_with(partial) && (partial.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+
+    let (first, others) = names.split_first()?;
+    let common = others.iter().fold(first.as_str(), |common, name| {
+        let agreed = common.char_indices().zip(name.chars()).find(|((_, a), b)| a != b).map_or(common.len().min(name.len()), |((i, _), _)| i);
+        &common[..agreed]
+    });
+    let completed = format!("{}{}{}", parent, common, if others.is_empty() { "/" } else { "" });
+    *text = completed;
+    (!others.is_empty()).then_some(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use file_manager_core::MemoryFs;
+
+    // A `root` folder with Desktop, Documents, Downloads and .config in it, and a file
+    fn sample() -> MemoryFs {
+        let fs = MemoryFs::new();
+        for dir in ["root", "root/Desktop", "root/Documents", "root/Downloads", "root/.config"] {
+            fs.create_dir(Path::new(dir)).unwrap();
+        }
+        fs.write(Path::new("root/Doc.txt"), b"").unwrap();
+        fs
+    }
+
+    fn completed(fs: &MemoryFs, text: &str) -> (String, Option<Vec<String>>) {
+        let mut text = text.to_owned();
+        let choices = complete(fs, Path::new(""), &mut text);
+        (text, choices)
+    }
+
+    #[test]
+    fn tilde_is_the_home_folder() {
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(expand("~"), Ok(home.clone()));
+        assert_eq!(expand("~/notes"), Ok(format!("{}/notes", home)));
+        assert_eq!(expand("~other/notes"), Ok(String::from("~other/notes")));  // Other users' homes aren't looked up
+        assert_eq!(expand("a/~"), Ok(String::from("a/~")));
+    }
+
+    #[test]
+    fn variables_are_replaced() {
+        std::env::set_var("FILE_MANGER_TEST_PROJECTS", "/work");
+        assert_eq!(expand("$FILE_MANGER_TEST_PROJECTS/app"), Ok(String::from("/work/app")));
+        assert_eq!(expand("${FILE_MANGER_TEST_PROJECTS}2"), Ok(String::from("/work2")));
+        assert_eq!(expand("a $ b/c$"), Ok(String::from("a $ b/c$")));  // No name after it
+    }
+
+    #[test]
+    fn unset_variables_are_errors() {
+        assert_eq!(expand("$FILE_MANGER_TEST_UNSET/app"), Err(String::from("$FILE_MANGER_TEST_UNSET is not set")));
+        assert_eq!(expand("${FILE_MANGER_TEST_UNSET"), Err(String::from("Missing } after ${")));
+    }
+
+    #[test]
+    fn completes_as_far_as_the_folders_agree() {
+        let fs = sample();
+        let (text, choices) = completed(&fs, "root/D");
+        assert_eq!(text, "root/D");
+        assert_eq!(choices, Some(vec![String::from("Desktop"), String::from("Documents"), String::from("Downloads")]));
+
+        let (text, choices) = completed(&fs, "root/Do");
+        assert_eq!(text, "root/Do");
+        assert_eq!(choices, Some(vec![String::from("Documents"), String::from("Downloads")]));
+    }
+
+    #[test]
+    fn a_single_folder_is_completed_with_a_slash() {
+        let fs = sample();
+        assert_eq!(completed(&fs, "root/Doc"), (String::from("root/Documents/"), None));
+        assert_eq!(completed(&fs, "ro"), (String::from("root/"), None));
+    }
+
+    #[test]
+    fn hidden_folders_only_when_asked_for() {
+        let fs = sample();
+        assert_eq!(completed(&fs, "root/."), (String::from("root/.config/"), None));
+        let (text, choices) = completed(&fs, "root/");
+        assert_eq!(text, "root/D");
+        assert_eq!(choices.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn completes_after_a_variable() {
+        let fs = sample();
+        std::env::set_var("FILE_MANGER_TEST_ROOT", "root");
+        assert_eq!(completed(&fs, "$FILE_MANGER_TEST_ROOT/Des"), (String::from("$FILE_MANGER_TEST_ROOT/Desktop/"), None));
+        assert_eq!(completed(&fs, "$FILE_MANGER_TEST_UNSET/Des"), (String::from("$FILE_MANGER_TEST_UNSET/Des"), None));
+    }
+}
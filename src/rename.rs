@@ -0,0 +1,86 @@
+use bevy_egui::egui;
+use file_manager_core::{names, FileSystem, NameError};
+use std::path::{Path, PathBuf};
+
+// Renaming a grid item or tree folder in place: its label turns into a text field until
+// Enter or a click elsewhere confirms it, or Escape cancels it.
+#[derive(Default)]
+pub struct InlineRename {
+    editing: Option<Editing>,
+}
+
+struct Editing {
+    path: PathBuf,  // The item being renamed
+    original: String,  // The name as first shown, possibly with non-UTF-8 bytes replaced
+    name: String,  // What's typed so far
+    focus: bool,  // Focus the field and select the name on the next frame
+    checked: Result<PathBuf, NameError>,  // `name` as last checked, so the disk is only asked again when it's edited
+}
+
+impl InlineRename {
+    pub fn start(&mut self, path: &Path) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        self.editing = Some(Editing { path: path.to_path_buf(), original: name.clone(), name, focus: true, checked: Ok(path.to_path_buf()) });
+    }
+
+    pub fn is_renaming(&self, path: &Path) -> bool {
+        self.editing.as_ref().is_some_and(|editing| editing.path == path)
+    }
+
+    // Draws the name field for the item being renamed. Returns `(from, to)` once a
+    // valid new name is confirmed; an unchanged name just ends the rename.
+    pub fn show(&mut self, ui: &mut egui::Ui, fs: &dyn FileSystem) -> Option<(PathBuf, PathBuf)> {
+        let editing = self.editing.as_mut()?;
+        let mut output = egui::TextEdit::singleline(&mut editing.name).desired_width(ui.available_width()).show(ui);
+        if std::mem::take(&mut editing.focus) {
+            output.response.request_focus();
+            // Select the name without its extension, like most file managers do
+            let stem = Path::new(&editing.name).file_stem().map_or(0, |stem| stem.to_string_lossy().chars().count());
+            let selection = egui::text::CCursorRange::two(egui::text::CCursor::new(0), egui::text::CCursor::new(stem));
+            output.state.cursor.set_char_range(Some(selection));
+            output.state.store(ui.ctx(), output.response.id);
+        }
+
+        // The lossy name shown for a non-UTF-8 one isn't the real name, so leaving it alone must not rename
+        let check = |editing: &Editing| {
+            if editing.name == editing.original {
+                Ok(editing.path.clone())
+            } else {
+                names::check_rename(fs, &editing.path, &editing.name)
+            }
+        };
+        if output.response.changed() {
+            editing.checked = check(editing);
+        }
+        if let Err(e) = &editing.checked {
+            ui.colored_label(ui.visuals().error_fg_color, egui::RichText::new(e.to_string()).small());
+        }
+
+        if !output.response.lost_focus() {
+            return None;
+        }
+        let (cancelled, entered) = ui.input(|i| (i.key_pressed(egui::Key::Escape), i.key_pressed(egui::Key::Enter)));
+        if cancelled {
+            self.editing = None;
+            return None;
+        }
+        // Checked once more before renaming, in case something took the name since
+        editing.checked = check(editing);
+        match &editing.checked {
+            Ok(to) if *to == editing.path => {}
+            Ok(to) => {
+                let renamed = (editing.path.clone(), to.clone());
+                self.editing = None;
+                return Some(renamed);
+            }
+            // Enter on a bad name keeps the field open so it can be fixed
+            Err(_) if entered => {
+                output.response.request_focus();
+                return None;
+            }
+            Err(_) => {}  // Clicking away from a bad name gives up on it
+        }
+        self.editing = None;
+        None
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use file_manager_core::Entry;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::listing::DirectoryListing;
+
+// The selected grid items. Actions from an item's context menu apply to the whole
+// selection when that item is part of it.
+#[derive(Resource, Default)]
+pub struct Selection {
+    items: HashSet<PathBuf>,  // Spelled like the listing's entries; a set, since every cell asks about it
+    anchor: Option<PathBuf>,  // Where Shift-click ranges start: the last item clicked without Shift
+    band: Option<RubberBand>,
+    checked: u64,  // The listing revision `retain_listed` last compared against
+}
+
+// A rectangle being dragged over the grid
+struct RubberBand {
+    origin: egui::Pos2,
+    base: HashSet<PathBuf>,  // What was selected before, kept when Ctrl is held
+}
+
+impl Selection {
+    // The selected paths, sorted so actions on them go in a predictable order
+    pub fn items(&self) -> Vec<PathBuf> {
+        let mut items: Vec<PathBuf> = self.items.iter().cloned().collect();
+        items.sort();
+        items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_selected(&self, path: &Path) -> bool {
+        self.items.contains(path)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.anchor = None;
+    }
+
+    // What an action on `path` applies to: the selection if it contains `path`, otherwise just `path`
+    pub fn targets(&self, path: &Path) -> Vec<PathBuf> {
+        if self.is_selected(path) {
+            self.items()
+        } else {
+            vec![path.to_path_buf()]
+        }
+    }
+
+    // A click on `path`: Ctrl toggles it, Shift selects the range from the anchor
+    // (added to the selection with Ctrl+Shift), a plain click selects only it
+    pub fn click(&mut self, path: &Path, modifiers: egui::Modifiers, entries: &[Entry]) {
+        if modifiers.shift {
+            let position = |path: &Path| entries.iter().position(|entry| entry.path == path);
+            let anchor = self.anchor.as_deref().and_then(position);
+            if let (Some(anchor), Some(clicked)) = (anchor, position(path)) {
+                if !modifiers.command {
+                    self.items.clear();
+                }
+                let range = &entries[anchor.min(clicked)..=anchor.max(clicked)];
+                self.items.extend(range.iter().map(|entry| entry.path.clone()));
+                return;
+            }
+        }
+
+        if modifiers.command {
+            if !self.items.remove(path) {
+                self.items.insert(path.to_path_buf());
+            }
+        } else {
+            self.items = HashSet::from([path.to_path_buf()]);
+        }
+        self.anchor = Some(path.to_path_buf());
+    }
+
+    pub fn select_all(&mut self, entries: &[Entry]) {
+        self.items = entries.iter().map(|entry| entry.path.clone()).collect();
+    }
+
+    // Drops items that are no longer listed, e.g. after they were deleted or the folder changed.
+    // Called every frame, so it only looks again once the listing has changed.
+    pub fn retain_listed(&mut self, listing: &DirectoryListing) {
+        if std::mem::replace(&mut self.checked, listing.revision()) == listing.revision() {
+            return;
+        }
+        if self.items.is_empty() && self.anchor.is_none() {
+            return;
+        }
+        let listed: HashSet<&Path> = listing.entries().iter().map(|entry| entry.path.as_path()).collect();
+        self.items.retain(|item| listed.contains(item.as_path()));
+        if self.anchor.as_deref().is_some_and(|anchor| !listed.contains(anchor)) {
+            self.anchor = None;
+        }
+    }
+
+    pub fn start_band(&mut self, origin: egui::Pos2, keep: bool) {
+        let base = if keep { self.items.clone() } else { HashSet::new() };
+        self.band = Some(RubberBand { origin, base });
+    }
+
+    // Selects what the band from its origin to `pointer` touches; returns the band to draw
+    pub fn update_band(&mut self, pointer: egui::Pos2, item_rects: &[(PathBuf, egui::Rect)]) -> Option<egui::Rect> {
+        let band = self.band.as_ref()?;
+        let rect = egui::Rect::from_two_pos(band.origin, pointer);
+        let mut items = band.base.clone();
+        items.extend(item_rects.iter().filter(|(_, item_rect)| rect.intersects(*item_rect)).map(|(path, _)| path.clone()));
+        self.items = items;
+        Some(rect)
+    }
+
+    pub fn end_band(&mut self) {
+        self.band = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Modifiers;
+
+    // Five files, a.txt to e.txt, in listing order
+    fn entries() -> Vec<Entry> {
+        ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|name| Entry {
+                path: PathBuf::from(format!("/x/{}.txt", name)),
+                is_dir: false,
+                is_symlink: false,
+                len: 0,
+                modified: None,
+                created: None,
+                mode: None,
+            })
+            .collect()
+    }
+
+    fn path(name: &str) -> PathBuf {
+        PathBuf::from(format!("/x/{}.txt", name))
+    }
+
+    #[test]
+    fn a_plain_click_selects_only_that_item() {
+        let (entries, mut selection) = (entries(), Selection::default());
+        selection.click(&path("a"), Modifiers::NONE, &entries);
+        selection.click(&path("c"), Modifiers::NONE, &entries);
+        assert_eq!(selection.items(), [path("c")]);
+    }
+
+    #[test]
+    fn ctrl_click_toggles() {
+        let (entries, mut selection) = (entries(), Selection::default());
+        selection.click(&path("a"), Modifiers::NONE, &entries);
+        selection.click(&path("c"), Modifiers::COMMAND, &entries);
+        assert_eq!(selection.items(), [path("a"), path("c")]);
+        selection.click(&path("a"), Modifiers::COMMAND, &entries);
+        assert_eq!(selection.items(), [path("c")]);
+    }
+
+    #[test]
+    fn shift_click_selects_the_range_from_the_anchor() {
+        let (entries, mut selection) = (entries(), Selection::default());
+        selection.click(&path("b"), Modifiers::NONE, &entries);
+        selection.click(&path("d"), Modifiers::SHIFT, &entries);
+        assert_eq!(selection.items(), [path("b"), path("c"), path("d")]);
+
+        // The anchor stays put, so the range can shrink or flip to the other side
+        selection.click(&path("a"), Modifiers::SHIFT, &entries);
+        assert_eq!(selection.items(), [path("a"), path("b")]);
+    }
+
+    #[test]
+    fn ctrl_shift_click_adds_the_range() {
+        let (entries, mut selection) = (entries(), Selection::default());
+        selection.click(&path("a"), Modifiers::NONE, &entries);
+        selection.click(&path("d"), Modifiers::COMMAND, &entries);
+        selection.click(&path("e"), Modifiers::SHIFT | Modifiers::COMMAND, &entries);
+        assert_eq!(selection.items(), [path("a"), path("d"), path("e")]);
+    }
+
+    #[test]
+    fn shift_click_without_an_anchor_is_a_plain_click() {
+        let (entries, mut selection) = (entries(), Selection::default());
+        selection.click(&path("c"), Modifiers::SHIFT, &entries);
+        assert_eq!(selection.items(), [path("c")]);
+
+        // It set the anchor, so the next Shift-click makes a range
+        selection.click(&path("e"), Modifiers::SHIFT, &entries);
+        assert_eq!(selection.items(), [path("c"), path("d"), path("e")]);
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::bookmarks::Bookmarks;
+use crate::notifications::Notice;
+use crate::sounds::SoundSettings;
+use crate::view::{FolderViews, IconSize};
+
+// User preferences, kept in `settings.ron` in the config folder
+// ($XDG_CONFIG_HOME/file_manger, or ~/.config/file_manger) and saved whenever they change.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = match load() {
+            Ok(settings) => settings,
+            Err(e) => {
+                app.add_event::<Notice>()
+                    .world_mut()
+                    .send_event(Notice::error(format!("Couldn't read settings, using the defaults: {}", e)));
+                Settings::default()
+            }
+        };
+        app.insert_resource(settings).add_systems(Last, save_settings);
+    }
+}
+
+// Missing fields take their defaults, so older settings files keep working
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub sounds: SoundSettings,
+    pub bookmarks: Bookmarks,
+    pub folder_views: FolderViews,  // Icons or details, and the sort order, of every folder set up differently
+    pub icon_size: IconSize,  // Of the icons in every folder
+    pub jail: Option<PathBuf>,  // Keeps the app inside this folder; only set by editing the file
+}
+
+fn settings_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("file_manger").join("settings.ron"))
+}
+
+// Reads the settings file; a missing file just means nothing was changed yet
+fn load() -> Result<Settings, String> {
+    let Some(path) = settings_path() else {
+        return Ok(Settings::default());
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(text) => ron::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+fn save(settings: &Settings) -> Result<(), String> {
+    let path = settings_path().ok_or("HOME is not set")?;
+    let text = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Writes the settings back after any change made during the frame
+fn save_settings(settings: Res<Settings>, mut notices: EventWriter<Notice>) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    if let Err(e) = save(&settings) {
+        notices.send(Notice::error(format!("Couldn't save settings: {}", e)));
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::audio::{AudioSource, Volume};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::settings::Settings;
+
+// Short sounds for things happening in the file manager, played through Bevy's audio.
+// The clips are compiled into the binary like the icons, so no asset folder is needed.
+// Which clip plays for what, the volume and muting are part of the settings.
+pub struct SoundsPlugin;
+
+impl Plugin for SoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySound>()
+            .init_resource::<SoundSettingsView>()
+            .add_systems(Startup, load_clips)
+            .add_systems(Update, sound_settings_window)
+            .add_systems(Last, play_sounds);
+    }
+}
+
+// Something that can make a sound
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SoundEvent {
+    OpenFolder,
+    OpenFile,
+    Delete,  // Items moved to the trash
+    Move,  // A move finished, by paste or drag and drop
+    Import,  // Files dropped in from another program finished copying
+}
+
+impl SoundEvent {
+    const ALL: [SoundEvent; 5] = [
+        SoundEvent::OpenFolder,
+        SoundEvent::OpenFile,
+        SoundEvent::Delete,
+        SoundEvent::Move,
+        SoundEvent::Import,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SoundEvent::OpenFolder => "Open folder",
+            SoundEvent::OpenFile => "Open file",
+            SoundEvent::Delete => "Move to trash",
+            SoundEvent::Move => "Move",
+            SoundEvent::Import => "Drop from outside",
+        }
+    }
+
+    fn default_clip(self) -> Option<Clip> {
+        match self {
+            SoundEvent::OpenFolder | SoundEvent::OpenFile => Some(Clip::Open),
+            SoundEvent::Delete | SoundEvent::Move | SoundEvent::Import => Some(Clip::Woosh),
+        }
+    }
+}
+
+// The bundled sounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Clip {
+    Open,  // assets/open.mp3
+    Woosh,  // assets/woosh.mp3
+}
+
+impl Clip {
+    fn label(clip: Option<Clip>) -> &'static str {
+        match clip {
+            Some(Clip::Open) => "Open",
+            Some(Clip::Woosh) => "Woosh",
+            None => "Silent",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub muted: bool,
+    pub volume: f32,  // 0.0 to 1.0
+    clips: BTreeMap<SoundEvent, Option<Clip>>,  // Events left out play their default clip
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            volume: 0.8,
+            clips: SoundEvent::ALL.iter().map(|event| (*event, event.default_clip())).collect(),
+        }
+    }
+}
+
+impl SoundSettings {
+    fn clip(&self, event: SoundEvent) -> Option<Clip> {
+        self.clips.get(&event).copied().unwrap_or_else(|| event.default_clip())
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySound(pub SoundEvent);
+
+// Whether the Settings > Sounds window is showing
+#[derive(Resource, Default)]
+pub struct SoundSettingsView {
+    pub open: bool,
+    dragged_volume: Option<f32>,  // Kept out of the settings until the slider is let go, so they aren't saved every frame
+}
+
+#[derive(Resource)]
+struct Clips {
+    open: Handle<AudioSource>,
+    woosh: Handle<AudioSource>,
+}
+
+fn load_clips(mut commands: Commands, mut sources: ResMut<Assets<AudioSource>>) {
+    let mut add = |bytes: &'static [u8]| sources.add(AudioSource { bytes: Arc::from(bytes) });
+    let open = add(include_bytes!("assets/open.mp3"));
+    let woosh = add(include_bytes!("assets/woosh.mp3"));
+    commands.insert_resource(Clips { open, woosh });
+}
+
+fn play_sounds(mut commands: Commands, clips: Res<Clips>, settings: Res<Settings>, mut events: EventReader<PlaySound>) {
+    let sounds = &settings.sounds;
+    for PlaySound(event) in events.read() {
+        if sounds.muted {
+            continue;
+        }
+        let Some(clip) = sounds.clip(*event) else {
+            continue;
+        };
+        let handle = match clip {
+            Clip::Open => &clips.open,
+            Clip::Woosh => &clips.woosh,
+        };
+        let playback = PlaybackSettings::DESPAWN.with_volume(Volume::new(sounds.volume));
+        commands.spawn((AudioPlayer::new(handle.clone()), playback));
+    }
+}
+
+// Mute, volume and which clip plays for each event
+fn sound_settings_window(
+    mut contexts: EguiContexts,
+    mut view: ResMut<SoundSettingsView>,
+    settings: ResMut<Settings>,
+    mut play: EventWriter<PlaySound>,
+) {
+    if !view.open {
+        return;
+    }
+    // Edited on a copy, so the settings only count as changed (and get saved) when something differs
+    let mut sounds = settings.sounds.clone();
+
+    let SoundSettingsView { open, dragged_volume } = &mut *view;
+
+    egui::Window::new("Sounds")
+        .open(open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.checkbox(&mut sounds.muted, "Mute");
+            let mut volume = dragged_volume.unwrap_or(sounds.volume);
+            let slider = ui.add_enabled(!sounds.muted, egui::Slider::new(&mut volume, 0.0..=1.0).text("Volume"));
+            if slider.dragged() {
+                *dragged_volume = Some(volume);
+            } else {
+                sounds.volume = volume;  // Let go, or changed with the keyboard
+                *dragged_volume = None;
+            }
+            ui.separator();
+
+            egui::Grid::new("sound_events").num_columns(3).show(ui, |ui| {
+                for event in SoundEvent::ALL {
+                    ui.label(event.label());
+                    let mut clip = sounds.clip(event);
+                    egui::ComboBox::from_id_salt(event).selected_text(Clip::label(clip)).show_ui(ui, |ui| {
+                        for choice in [Some(Clip::Open), Some(Clip::Woosh), None] {
+                            ui.selectable_value(&mut clip, choice, Clip::label(choice));
+                        }
+                    });
+                    sounds.clips.insert(event, clip);
+                    if ui.add_enabled(clip.is_some() && !sounds.muted, egui::Button::new("▶")).on_hover_text("Play").clicked() {
+                        play.send(PlaySound(event));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+    settings.map_unchanged(|settings| &mut settings.sounds).set_if_neq(sounds);
+}
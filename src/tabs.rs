@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use std::path::{Path, PathBuf};
+
+use crate::listing::DirectoryListing;
+use crate::navigation::Navigation;
+use crate::selection::Selection;
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// Several folders open at once, each tab with its own Back/Forward history, selection
+// and listing. The active tab's state lives in the `Navigation`, `Selection` and
+// `DirectoryListing` resources everything else works with; switching tabs swaps it
+// with the state parked in `Tabs`. Parked listings are kept up to date from the
+// watcher, so a tab shows its folder as it is the moment it's switched to.
+pub struct TabsPlugin;
+
+impl Plugin for TabsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tabs>()
+            .add_event::<TabAction>()
+            .add_systems(Update, (tab_shortcuts, apply_tab_actions).chain())
+            .add_systems(PostUpdate, update_parked_listings);
+    }
+}
+
+// Everything that belongs to one tab
+pub struct Tab {
+    navigation: Navigation,
+    selection: Selection,
+    listing: DirectoryListing,
+}
+
+impl Tab {
+    pub fn dir(&self) -> &Path {
+        self.navigation.current()
+    }
+}
+
+#[derive(Resource)]
+pub struct Tabs {
+    tabs: Vec<Option<Tab>>,  // In tab bar order; `None` is the active tab, whose state is in the resources
+    closed: Vec<Tab>,  // Most recently closed last, for reopening
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Self { tabs: vec![None], closed: Vec::new() }
+    }
+}
+
+impl Tabs {
+    pub fn active(&self) -> usize {
+        self.tabs.iter().position(Option::is_none).unwrap_or(0)
+    }
+
+    // The tabs that aren't showing, with their position in the tab bar
+    pub fn inactive(&self) -> impl Iterator<Item = (usize, &Tab)> {
+        self.tabs.iter().enumerate().filter_map(|(index, tab)| Some((index, tab.as_ref()?)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn can_reopen(&self) -> bool {
+        !self.closed.is_empty()
+    }
+}
+
+// Something to do with the tabs, from the tab bar, a menu or a shortcut
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub enum TabAction {
+    OpenInBackground(PathBuf),  // A new tab next to the active one, which stays active
+    Duplicate,  // A new tab showing the same folder, switched to
+    Switch(usize),
+    Close(usize),
+    Reopen,  // The tab closed last
+    Move { from: usize, to: usize },
+}
+
+// Ctrl+T opens a tab, Ctrl+W closes it, Ctrl+Shift+T brings back the last one closed,
+// Ctrl+Tab and Ctrl+Shift+Tab go through them. Not while typing.
+fn tab_shortcuts(mut contexts: EguiContexts, tabs: Res<Tabs>, mut actions: EventWriter<TabAction>) {
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let shift = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+    let (reopen, new, close, previous, next) = ctx.input_mut(|i| {
+        // The shifted combinations first, the plain ones would match them too
+        let reopen = i.consume_key(shift, egui::Key::T);
+        let previous = i.consume_key(shift, egui::Key::Tab);
+        let new = i.consume_key(egui::Modifiers::COMMAND, egui::Key::T);
+        let close = i.consume_key(egui::Modifiers::COMMAND, egui::Key::W);
+        let next = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Tab);
+        (reopen, new, close, previous, next)
+    });
+
+    let (active, count) = (tabs.active(), tabs.len());
+    if reopen {
+        actions.send(TabAction::Reopen);
+    }
+    if new {
+        actions.send(TabAction::Duplicate);
+    }
+    if close {
+        actions.send(TabAction::Close(active));
+    }
+    if previous {
+        actions.send(TabAction::Switch((active + count - 1) % count));
+    }
+    if next {
+        actions.send(TabAction::Switch((active + 1) % count));
+    }
+}
+
+// Performs the tab actions, swapping tab state in and out of the resources
+fn apply_tab_actions(
+    mut tabs: ResMut<Tabs>,
+    mut navigation: ResMut<Navigation>,
+    mut selection: ResMut<Selection>,
+    mut listing: ResMut<DirectoryListing>,
+    mut actions: EventReader<TabAction>,
+) {
+    for action in actions.read() {
+        let active = tabs.active();
+        match action {
+            TabAction::OpenInBackground(dir) => {
+                let tab = Tab { navigation: navigation.fresh(dir), selection: Selection::default(), listing: DirectoryListing::default() };
+                tabs.tabs.insert(active + 1, Some(tab));
+            }
+            TabAction::Duplicate => {
+                let tab = Tab { navigation: navigation.fresh(navigation.current()), selection: Selection::default(), listing: DirectoryListing::default() };
+                tabs.tabs.insert(active + 1, Some(tab));
+                switch(&mut tabs, active + 1, &mut navigation, &mut selection, &mut listing);
+            }
+            TabAction::Switch(index) => switch(&mut tabs, *index, &mut navigation, &mut selection, &mut listing),
+            TabAction::Close(index) if tabs.len() > 1 && *index < tabs.len() => {
+                if *index == active {
+                    // Show the tab to the right, or to the left when closing the last one
+                    let next = if *index + 1 < tabs.len() { *index + 1 } else { *index - 1 };
+                    switch(&mut tabs, next, &mut navigation, &mut selection, &mut listing);
+                }
+                if let Some(tab) = tabs.tabs.remove(*index) {
+                    tabs.closed.push(tab);
+                }
+            }
+            TabAction::Close(_) => {}  // The last tab stays open
+            TabAction::Reopen => {
+                if let Some(mut tab) = tabs.closed.pop() {
+                    tab.listing.invalidate();  // Nothing kept it up to date while it was closed
+                    tabs.tabs.insert(active + 1, Some(tab));
+                    switch(&mut tabs, active + 1, &mut navigation, &mut selection, &mut listing);
+                }
+            }
+            TabAction::Move { from, to } if *from < tabs.len() && *to < tabs.len() => {
+                let tab = tabs.tabs.remove(*from);
+                tabs.tabs.insert(*to, tab);
+            }
+            TabAction::Move { .. } => {}
+        }
+    }
+}
+
+// Parks the active tab's state and brings in the state of the tab at `index`
+fn switch(
+    tabs: &mut Tabs,
+    index: usize,
+    navigation: &mut Navigation,
+    selection: &mut Selection,
+    listing: &mut DirectoryListing,
+) {
+    let active = tabs.active();
+    let Some(Some(mut tab)) = tabs.tabs.get_mut(index).map(Option::take) else {
+        return;  // Already active, or gone
+    };
+    std::mem::swap(navigation, &mut tab.navigation);
+    std::mem::swap(selection, &mut tab.selection);
+    std::mem::swap(listing, &mut tab.listing);
+    tabs.tabs[active] = Some(tab);
+}
+
+// Patches the listings of the tabs in the background, like `ListingPlugin` does for the
+// active one. A scan still running when the tab was parked is picked up when it comes back.
+fn update_parked_listings(storage: Res<Storage>, mut tabs: ResMut<Tabs>, mut changes: EventReader<FsChange>) {
+    for change in changes.read() {
+        for tab in tabs.tabs.iter_mut().flatten() {
+            tab.listing.apply_change(&*storage.0, change);
+        }
+    }
+}
+
+// The row of tabs above the path bar. Click to switch, middle-click or × to close,
+// drag to reorder. Runs after the menu bar so it ends up right under it.
+pub fn tab_bar(
+    mut contexts: EguiContexts,
+    tabs: Res<Tabs>,
+    navigation: Res<Navigation>,
+    mut actions: EventWriter<TabAction>,
+) {
+    // Marks a tab being dragged to a new place
+    struct DraggedTab(usize);
+
+    let active = tabs.active();
+    let closable = tabs.len() > 1;
+    egui::TopBottomPanel::top("tab_bar").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            for index in 0..tabs.len() {
+                let dir = match tabs.tabs[index].as_ref() {
+                    Some(tab) => tab.dir(),
+                    None => navigation.current(),
+                };
+                let name = dir.file_name().unwrap_or(dir.as_os_str()).to_string_lossy();
+
+                let tab = ui.selectable_label(index == active, name.as_ref()).interact(egui::Sense::drag()).on_hover_text(dir.display().to_string());
+                if tab.clicked() {
+                    actions.send(TabAction::Switch(index));
+                }
+                if tab.middle_clicked() && closable {
+                    actions.send(TabAction::Close(index));
+                }
+                if tab.drag_started() {
+                    tab.dnd_set_drag_payload(DraggedTab(index));
+                }
+                if let Some(dragged) = tab.dnd_release_payload::<DraggedTab>() {
+                    actions.send(TabAction::Move { from: dragged.0, to: index });
+                }
+                tab.context_menu(|ui| {
+                    if ui.add_enabled(index > 0, egui::Button::new("Move Left")).clicked() {
+                        actions.send(TabAction::Move { from: index, to: index - 1 });
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(index + 1 < tabs.len(), egui::Button::new("Move Right")).clicked() {
+                        actions.send(TabAction::Move { from: index, to: index + 1 });
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(closable, egui::Button::new("Close Tab").shortcut_text("Ctrl+W")).clicked() {
+                        actions.send(TabAction::Close(index));
+                        ui.close_menu();
+                    }
+                });
+                if closable && ui.small_button("×").on_hover_text("Close Tab").clicked() {
+                    actions.send(TabAction::Close(index));
+                }
+                ui.separator();
+            }
+
+            if ui.small_button("+").on_hover_text("New Tab (Ctrl+T)").clicked() {
+                actions.send(TabAction::Duplicate);
+            }
+            if ui.add_enabled(tabs.can_reopen(), egui::Button::new("↺").small()).on_hover_text("Reopen Closed Tab (Ctrl+Shift+T)").clicked() {
+                actions.send(TabAction::Reopen);
+            }
+        });
+    });
+}
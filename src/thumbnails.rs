@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::thumbnails::{self, MTIME_KEY, URI_KEY};
+use file_manager_core::{paths, Entry, FileSystem, ThumbnailCache, ThumbnailSize};
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::Storage;
+
+const MAX_JOBS: usize = 4;  // Thumbnails made at once, so the scan and transfers still get threads
+const MAX_SOURCE_LEN: u64 = 64 * 1024 * 1024;  // Bigger images keep the plain icon rather than eat memory
+const KEEP_UNSEEN: Duration = Duration::from_secs(30);  // How long a thumbnail stays loaded once it scrolled out of view
+
+// Thumbnails of PNG, JPEG, GIF, WebP and BMP files in place of the plain file icon.
+// They're made in the background for the items on screen, and kept in the shared
+// freedesktop.org cache (~/.cache/thumbnails), so other programs reuse ours and we
+// reuse theirs. A thumbnail is made again when the file's modification time changes.
+pub struct ThumbnailsPlugin;
+
+impl Plugin for ThumbnailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Thumbnails::new(ThumbnailCache::home()))
+            .add_systems(PostUpdate, run_thumbnail_jobs);  // After the grid asked for this frame's thumbnails
+    }
+}
+
+#[derive(Resource)]
+pub struct Thumbnails {
+    cache: Option<ThumbnailCache>,  // None without a home folder; thumbnails are then only kept in memory
+    known: HashMap<(PathBuf, ThumbnailSize), Thumbnail>,
+    wanted: Vec<(PathBuf, ThumbnailSize, SystemTime)>,  // Asked for this frame and not being made yet
+}
+
+struct Thumbnail {
+    made_from: Option<SystemTime>,  // Modification time of the file the texture shows, None until tried
+    texture: Option<egui::TextureHandle>,  // None while it's being made, or if the file isn't a readable image
+    job: Option<Task<(SystemTime, Option<egui::ColorImage>)>>,
+    last_used: Instant,
+}
+
+impl Thumbnails {
+    fn new(cache: Option<ThumbnailCache>) -> Self {
+        Self { cache, known: HashMap::new(), wanted: Vec::new() }
+    }
+
+    // The thumbnail to draw for `entry`, if it's an image and its thumbnail is ready.
+    // Asking is what gets it made, so only the items on screen get thumbnails.
+    pub fn get(&mut self, entry: &Entry, size: ThumbnailSize) -> Option<egui::TextureHandle> {
+        let modified = entry.modified?;  // The cache can't tell stale thumbnails apart without it
+        if entry.is_dir || entry.len > MAX_SOURCE_LEN || !thumbnails::is_image(&entry.path) {
+            return None;
+        }
+        let thumbnail = self.known.entry((entry.path.clone(), size)).or_insert_with(|| Thumbnail {
+            made_from: None,
+            texture: None,
+            job: None,
+            last_used: Instant::now(),
+        });
+        thumbnail.last_used = Instant::now();
+        if thumbnail.job.is_none() && thumbnail.made_from != Some(modified) {
+            self.wanted.push((entry.path.clone(), size, modified));
+        }
+        thumbnail.texture.clone()  // An out-of-date one is still better than the plain icon until the new one is ready
+    }
+}
+
+// Collects finished thumbnails, forgets the ones nobody looked at for a while and starts
+// on what was asked for this frame
+fn run_thumbnail_jobs(mut contexts: EguiContexts, storage: Res<Storage>, mut thumbnails: ResMut<Thumbnails>) {
+    let thumbnails = &mut *thumbnails;
+    let ctx = contexts.ctx_mut();
+    for ((path, _), thumbnail) in thumbnails.known.iter_mut() {
+        let Some(job) = thumbnail.job.as_mut() else {
+            continue;
+        };
+        let Some((modified, image)) = block_on(future::poll_once(job)) else {
+            continue;  // Still working
+        };
+        thumbnail.job = None;
+        thumbnail.made_from = Some(modified);
+        thumbnail.texture = image.map(|image| ctx.load_texture(path.to_string_lossy(), image, egui::TextureOptions::LINEAR));
+    }
+    thumbnails.known.retain(|_, thumbnail| thumbnail.job.is_some() || thumbnail.last_used.elapsed() < KEEP_UNSEEN);
+
+    let mut running = thumbnails.known.values().filter(|thumbnail| thumbnail.job.is_some()).count();
+    for (path, size, modified) in thumbnails.wanted.drain(..) {
+        if running >= MAX_JOBS {
+            break;  // The rest are asked for again next frame if they're still on screen
+        }
+        let Some(thumbnail) = thumbnails.known.get_mut(&(path.clone(), size)).filter(|thumbnail| thumbnail.job.is_none()) else {
+            continue;  // Asked for twice, by both panes showing the same folder
+        };
+        let fs = Arc::clone(&storage.0);
+        let cache = thumbnails.cache.clone();
+        thumbnail.job = Some(AsyncComputeTaskPool::get().spawn(async move {
+            // Unreadable or broken images just keep the plain icon
+            (modified, make_thumbnail(&*fs, cache.as_ref(), &path, modified, size).ok())
+        }));
+        running += 1;
+    }
+}
+
+// Loads the thumbnail of the image at `path` from the cache, or makes it from the image
+// and stores it there
+fn make_thumbnail(
+    fs: &dyn FileSystem,
+    cache: Option<&ThumbnailCache>,
+    path: &Path,
+    modified: SystemTime,
+    size: ThumbnailSize,
+) -> Result<egui::ColorImage, String> {
+    let absolute = paths::normalize(path).map_err(|e| e.to_string())?;
+    let uri = thumbnails::file_uri(&absolute);
+    let mtime = thumbnails::mtime_text(modified).ok_or("modified before 1970")?;
+    // The spec asks never to thumbnail thumbnails, but they can still be shown as they are
+    let cached = cache.filter(|cache| !cache.contains(&absolute)).map(|cache| cache.path(size, &uri));
+    if let Some(image) = cached.as_deref().and_then(|cached| read_cached(cached, &uri, &mtime)) {
+        return Ok(image);
+    }
+
+    let bytes = fs.read(path).map_err(|e| e.to_string())?;  // Through `Storage`, so the jail applies
+    let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    let edge = size.pixels();
+    let image = if image.width() > edge || image.height() > edge { image.thumbnail(edge, edge) } else { image }.to_rgba8();
+    if let Some(cached) = cached {
+        let _ = write_cached(&cached, &image, &uri, &mtime);  // A read-only cache only means making it again next time
+    }
+    Ok(egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw()))
+}
+
+// A cached thumbnail, if it's of the file at `uri` as it was at `mtime`
+fn read_cached(cached: &Path, uri: &str, mtime: &str) -> Option<egui::ColorImage> {
+    let bytes = std::fs::read(cached).ok()?;
+    let reader = png::Decoder::new(Cursor::new(&bytes)).read_info().ok()?;
+    let text = |key: &str| reader.info().uncompressed_latin1_text.iter().find(|chunk| chunk.keyword == key).map(|chunk| chunk.text.as_str());
+    if text(URI_KEY) != Some(uri) || text(MTIME_KEY) != Some(mtime) {
+        return None;  // Made from an older version of the file, or another file with the same MD5
+    }
+    let image = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png).ok()?.to_rgba8();
+    Some(egui::ColorImage::from_rgba_unmultiplied([image.width() as usize, image.height() as usize], image.as_raw()))
+}
+
+// Stores a thumbnail the way the spec asks: readable only by the user, and written to a
+// temporary file first so other programs never see half of it
+fn write_cached(cached: &Path, image: &image::RgbaImage, uri: &str, mtime: &str) -> io::Result<()> {
+    let dir = cached.parent().ok_or(io::ErrorKind::NotFound)?;
+    create_private_dir(dir)?;
+    let temporary = cached.with_extension(format!("{}.tmp", std::process::id()));
+    let result = write_png(&temporary, image, uri, mtime).and_then(|()| std::fs::rename(&temporary, cached));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+    result
+}
+
+fn write_png(path: &Path, image: &image::RgbaImage, uri: &str, mtime: &str) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = BufWriter::new(options.open(path)?);
+
+    let mut encoder = png::Encoder::new(&mut file, image.width(), image.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.add_text_chunk(URI_KEY.to_string(), uri.to_string()).map_err(io::Error::other)?;
+    encoder.add_text_chunk(MTIME_KEY.to_string(), mtime.to_string()).map_err(io::Error::other)?;
+    encoder.add_text_chunk(String::from("Software"), String::from("file_manger")).map_err(io::Error::other)?;
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(image.as_raw()).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    file.flush()
+}
+
+// The cache folders hold thumbnails of private files, so only the user may look inside
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
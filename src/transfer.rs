@@ -0,0 +1,366 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::error::Result as FsResult;
+use file_manager_core::ops::{self, Progress};
+use file_manager_core::{names, FsError, Operation};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::history::History;
+use crate::notifications::Notice;
+use crate::sounds::{PlaySound, SoundEvent};
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// How long each frame may spend moving items before the UI gets to redraw
+const FRAME_BUDGET: Duration = Duration::from_millis(30);
+// Copies that finish quicker than this don't flash a progress window
+const PROGRESS_DELAY: Duration = Duration::from_millis(300);
+
+// Copying and moving items between folders: the internal clipboard, a queue that
+// works through one item at a time, and a dialog for name collisions.
+// Copies, and moves to another drive, run on the async compute pool, so large trees
+// don't freeze the window.
+pub struct TransferPlugin;
+
+impl Plugin for TransferPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Clipboard>()
+            .init_resource::<Transfers>()
+            .add_event::<TransferRequest>()
+            .add_systems(Update, (queue_transfers, transfer_windows, run_transfers).chain());
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransferMode {
+    #[default]
+    Copy,
+    Move,
+}
+
+// Items copied or cut from the grid, waiting to be pasted
+#[derive(Resource, Default)]
+pub struct Clipboard {
+    items: Vec<PathBuf>,
+    mode: TransferMode,  // Move for cut items
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, items: Vec<PathBuf>) {
+        self.items = items;
+        self.mode = TransferMode::Copy;
+    }
+
+    pub fn cut(&mut self, items: Vec<PathBuf>) {
+        self.items = items;
+        self.mode = TransferMode::Move;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    // Whether `path` was cut and is waiting to be moved, so the grid can dim it
+    pub fn is_cut(&self, path: &Path) -> bool {
+        self.mode == TransferMode::Move && self.items.iter().any(|item| item == path)
+    }
+
+    // The request that pastes the clipboard into `dir`. Cut items can only be pasted once.
+    pub fn paste(&mut self, dir: &Path) -> TransferRequest {
+        let sources = match self.mode {
+            TransferMode::Copy => self.items.clone(),
+            TransferMode::Move => std::mem::take(&mut self.items),
+        };
+        TransferRequest {
+            sources,
+            dest: dir.to_path_buf(),
+            mode: self.mode,
+            from_outside: false,
+        }
+    }
+}
+
+// Copy or move `sources` into the folder `dest`
+#[derive(Event, Clone, Debug)]
+pub struct TransferRequest {
+    pub sources: Vec<PathBuf>,
+    pub dest: PathBuf,
+    pub mode: TransferMode,
+    pub from_outside: bool,  // Dropped in from another program
+}
+
+// What to do when an item with the same name is already in the destination
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Resolution {
+    Overwrite,  // Trash the existing item, so it can be undone
+    Skip,
+    KeepBoth,  // Give the new item a numbered name
+}
+
+// A request being worked through
+struct Transfer {
+    pending: VecDeque<PathBuf>,
+    dest: PathBuf,
+    mode: TransferMode,
+    total: usize,
+    apply_to_all: Option<Resolution>,  // Chosen with "Apply to all" in the collision dialog
+    from_outside: bool,
+}
+
+// One item being copied in the background, or moved to another drive
+struct Running {
+    mode: TransferMode,
+    from: PathBuf,
+    to: PathBuf,
+    progress: Arc<Progress>,
+    started: Instant,
+    task: Task<FsResult<()>>,
+}
+
+// An item waiting for the user to pick a `Resolution`
+struct Collision {
+    from: PathBuf,
+    target: PathBuf,
+    apply_to_all: bool,  // State of the checkbox
+    resolution: Option<Resolution>,
+}
+
+// Transfers in progress, oldest first; only the first one runs
+#[derive(Resource, Default)]
+struct Transfers {
+    queue: VecDeque<Transfer>,
+    collision: Option<Collision>,
+    running: Option<Running>,
+}
+
+fn queue_transfers(mut requests: EventReader<TransferRequest>, mut transfers: ResMut<Transfers>) {
+    for request in requests.read() {
+        if request.sources.is_empty() {
+            continue;
+        }
+        transfers.queue.push_back(Transfer {
+            pending: request.sources.iter().cloned().collect(),
+            dest: request.dest.clone(),
+            mode: request.mode,
+            total: request.sources.len(),
+            apply_to_all: None,
+            from_outside: request.from_outside,
+        });
+    }
+}
+
+// The collision dialog, and a progress window for transfers that take more than a moment
+fn transfer_windows(mut contexts: EguiContexts, mut transfers: ResMut<Transfers>) {
+    let ctx = contexts.ctx_mut();
+    let Transfers { queue, collision, running } = &mut *transfers;
+    let Some(transfer) = queue.front_mut() else {
+        return;
+    };
+
+    if let Some(asked) = collision.as_mut().filter(|asked| asked.resolution.is_none()) {
+        let mut cancel = false;
+        egui::Window::new("Item already exists")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                let name = asked.target.file_name().unwrap_or_default().to_string_lossy();
+                ui.label(format!("\"{}\" already exists in {}.", name, transfer.dest.display()));
+                if !transfer.pending.is_empty() {
+                    ui.checkbox(&mut asked.apply_to_all, format!("Apply to all ({} more)", transfer.pending.len()));
+                }
+                ui.horizontal(|ui| {
+                    for (label, resolution) in [
+                        ("Overwrite", Resolution::Overwrite),
+                        ("Skip", Resolution::Skip),
+                        ("Keep Both", Resolution::KeepBoth),
+                    ] {
+                        if ui.button(label).clicked() {
+                            asked.resolution = Some(resolution);
+                            if asked.apply_to_all {
+                                transfer.apply_to_all = Some(resolution);
+                            }
+                        }
+                    }
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if cancel {
+            // Whatever was already copied stays, and can be undone from the history
+            queue.pop_front();
+            *collision = None;
+        }
+        return;
+    }
+
+    // Only shown once the transfer has taken a moment
+    let job = running.as_ref().filter(|running| running.started.elapsed() >= PROGRESS_DELAY);
+    if transfer.total == 1 && job.is_none() {
+        return;
+    }
+    let verb = match transfer.mode {
+        TransferMode::Copy => "Copying",
+        TransferMode::Move => "Moving",
+    };
+    egui::Window::new("Transfer")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -20.0))
+        .show(ctx, |ui| {
+            if transfer.total > 1 {
+                let done = transfer.total - transfer.pending.len();
+                ui.label(format!("{} {} of {} items to {}", verb, done, transfer.total, transfer.dest.display()));
+                ui.add(egui::ProgressBar::new(done as f32 / transfer.total as f32).desired_width(300.0));
+            }
+            // Folders get a bar of their own, counting the files inside
+            if let Some(job) = job {
+                let (done, total) = (job.progress.done(), job.progress.total().max(1));
+                let name = job.from.file_name().unwrap_or_default().to_string_lossy();
+                ui.label(format!("{} {}: {} of {} files", verb, name, done, total));
+                ui.add(egui::ProgressBar::new(done as f32 / total as f32).desired_width(300.0));
+            }
+        });
+    ctx.request_repaint();  // Keep the bars moving while nothing else happens
+}
+
+// Copies or moves queued items until the frame budget runs out or a collision needs an answer
+fn run_transfers(
+    storage: Res<Storage>,
+    mut history: ResMut<History>,
+    mut transfers: ResMut<Transfers>,
+    mut fs_changes: EventWriter<FsChange>,
+    mut notices: EventWriter<Notice>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let fs = &*storage.0;
+    let started = Instant::now();
+    let Transfers { queue, collision, running } = &mut *transfers;
+
+    // The item in the background has to finish before the next one starts
+    if let Some(mut job) = running.take() {
+        match (block_on(future::poll_once(&mut job.task)), job.mode) {
+            (None, _) => {
+                *running = Some(job);
+                return;
+            }
+            // Recorded only now that it's done, so Ctrl+Z can trash the copy or move it back
+            (Some(Ok(())), TransferMode::Copy) => {
+                info!("Copied {:?} to {:?}", job.from, job.to);
+                history.0.record(fs, Operation::Copy { from: job.from, to: job.to.clone(), trashed: None });
+                fs_changes.send(FsChange::Created(job.to));
+            }
+            (Some(Ok(())), TransferMode::Move) => {
+                info!("Moved {:?} to {:?}", job.from, job.to);
+                history.0.record(fs, Operation::Move { from: job.from.clone(), to: job.to.clone() });
+                fs_changes.send(FsChange::Renamed { from: job.from, to: job.to });
+            }
+            (Some(Err(e)), TransferMode::Copy) => {
+                notices.send(Notice::error(format!("Couldn't copy {}: {}", job.from.display(), e)));
+            }
+            (Some(Err(e)), TransferMode::Move) => {
+                notices.send(Notice::error(format!("Couldn't move {}: {}", job.from.display(), e)));
+            }
+        }
+    }
+
+    while started.elapsed() < FRAME_BUDGET {
+        let Some(transfer) = queue.front_mut() else {
+            return;
+        };
+
+        // Either the answer to the collision dialog, or the next item
+        let (from, target, resolution) = match collision.take() {
+            Some(Collision { from, target, resolution: Some(resolution), .. }) => (from, target, Some(resolution)),
+            Some(unanswered) => {
+                *collision = Some(unanswered);
+                return;
+            }
+            None => {
+                let Some(from) = transfer.pending.pop_front() else {
+                    if transfer.from_outside {
+                        sounds.send(PlaySound(SoundEvent::Import));
+                    } else if transfer.mode == TransferMode::Move {
+                        sounds.send(PlaySound(SoundEvent::Move));
+                    }
+                    queue.pop_front();
+                    continue;
+                };
+                let Some(name) = from.file_name() else {
+                    continue;
+                };
+                let target = transfer.dest.join(name);
+                if transfer.mode == TransferMode::Move && target == from {
+                    continue;  // Already there
+                }
+                if fs.metadata(&target).is_err() {
+                    (from, target, None)
+                } else if let Some(resolution) = transfer.apply_to_all {
+                    (from, target, Some(resolution))
+                } else {
+                    *collision = Some(Collision { from, target, apply_to_all: false, resolution: None });
+                    return;
+                }
+            }
+        };
+
+        let target = match resolution {
+            None => target,
+            Some(Resolution::Skip) => continue,
+            Some(Resolution::KeepBoth) => names::keep_both_name(fs, &target),
+            Some(Resolution::Overwrite) if target == from => {
+                notices.send(Notice::error(format!("Can't overwrite {} with itself", from.display())));
+                continue;
+            }
+            // Trashing the target would take `from` along with it
+            Some(Resolution::Overwrite) if ops::check_not_into_itself(&target, &from).is_err() => {
+                notices.send(Notice::error(format!("Can't overwrite {} with something inside it", target.display())));
+                continue;
+            }
+            Some(Resolution::Overwrite) => match history.0.delete(fs, &target) {
+                Ok(()) => {
+                    fs_changes.send(FsChange::Removed(target.clone()));
+                    target
+                }
+                Err(e) => {
+                    notices.send(Notice::error(format!("Couldn't overwrite {}: {}", target.display(), e)));
+                    continue;
+                }
+            },
+        };
+
+        // Renaming is instant, so moves within a drive are done right here
+        if transfer.mode == TransferMode::Move {
+            match ops::check_not_into_itself(&from, &target).and_then(|()| ops::rename(fs, &from, &target)) {
+                Ok(()) => {
+                    info!("Moved {:?} to {:?}", from, target);
+                    history.0.record(fs, Operation::Move { from: from.clone(), to: target.clone() });
+                    fs_changes.send(FsChange::Renamed { from, to: target });
+                    continue;
+                }
+                Err(FsError::CrossesDevices(_)) => {}  // Copied over and removed in the background below
+                Err(e) => {
+                    notices.send(Notice::error(format!("Couldn't move {}: {}", from.display(), e)));
+                    continue;
+                }
+            }
+        }
+
+        let mode = transfer.mode;
+        let progress = Arc::new(Progress::default());
+        let task = {
+            let (fs, from, to, progress) = (storage.0.clone(), from.clone(), target.clone(), progress.clone());
+            AsyncComputeTaskPool::get().spawn(async move {
+                match mode {
+                    TransferMode::Copy => ops::copy_with_progress(&*fs, &from, &to, &progress),
+                    TransferMode::Move => ops::move_item_with_progress(&*fs, &from, &to, &progress),
+                }
+            })
+        };
+        *running = Some(Running { mode, from, to: target, progress, started: Instant::now(), task });
+        return;  // Picked up above once it's done
+    }
+}
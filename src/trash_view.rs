@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use file_manager_core::{Trash, TrashItem};
+
+use crate::jail::JailRoot;
+use crate::listing::DirectoryListing;
+use crate::notifications::Notice;
+use crate::watcher::FsChange;
+use crate::Storage;
+
+// The recycle bin: deleting from the grid moves items into the user's trash,
+// and the Trash window lets them be restored or purged for good.
+pub struct TrashPlugin;
+
+impl Plugin for TrashPlugin {
+    fn build(&self, app: &mut App) {
+        // Inside a jail the trash has to be inside too, and shouldn't show what others deleted
+        let jailed_trash = app.world().resource::<JailRoot>().data_dir().map(|dir| Trash::new(dir.join("trash")));
+        let trash = jailed_trash.or_else(Trash::home).unwrap_or_else(|| {
+            app.add_event::<Notice>()
+                .world_mut()
+                .send_event(Notice::error("Couldn't find your trash folder (HOME is not set), using a temporary one"));
+            Trash::new(std::env::temp_dir().join("file_manger-trash"))
+        });
+        app.insert_resource(TrashBin(trash))
+            .init_resource::<TrashView>()
+            .add_systems(Update, trash_window);
+    }
+}
+
+// The trash every delete goes through: the user's own, one inside the jail, or a temporary one if there's no home folder
+#[derive(Resource)]
+pub struct TrashBin(pub Trash);
+
+// State of the Trash window
+#[derive(Resource, Default)]
+pub struct TrashView {
+    open: bool,
+    items: Option<Vec<TrashItem>>,  // None until read; re-read after every restore or purge
+    confirm: Option<Purge>,  // Permanent deletion waiting for the user to confirm
+}
+
+enum Purge {
+    One(TrashItem),
+    All,
+}
+
+impl TrashView {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.items = None;  // Pick up anything trashed since it was last shown
+    }
+}
+
+// Draws the Trash window with restore and permanent delete actions
+fn trash_window(
+    mut contexts: EguiContexts,
+    storage: Res<Storage>,
+    trash: Res<TrashBin>,
+    listing: Res<DirectoryListing>,
+    mut view: ResMut<TrashView>,
+    mut fs_changes: EventWriter<FsChange>,
+    mut notices: EventWriter<Notice>,
+) {
+    if !view.open {
+        return;
+    }
+    let fs = &*storage.0;
+    let ctx = contexts.ctx_mut();
+
+    let items = view.items.get_or_insert_with(|| {
+        trash.0.list(fs).unwrap_or_else(|e| {
+            notices.send(Notice::error(format!("Couldn't read the trash: {}", e)));
+            Vec::new()
+        })
+    }).clone();
+
+    let mut open = view.open;
+    let mut restore = None;
+    egui::Window::new("Trash")
+        .open(&mut open)
+        .default_size(egui::vec2(520.0, 360.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} item(s)", items.len()));
+                if ui.add_enabled(!items.is_empty(), egui::Button::new("Empty Trash")).clicked() {
+                    view.confirm = Some(Purge::All);
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                egui::Grid::new("trash_items").striped(true).num_columns(4).show(ui, |ui| {
+                    for item in &items {
+                        let icon = if item.is_dir { "📁" } else { "📄" };
+                        ui.label(format!("{} {}", icon, item.name()));
+                        ui.label(item.original_path.parent().map(|p| p.display().to_string()).unwrap_or_default())
+                            .on_hover_text(item.original_path.display().to_string());
+                        ui.label(item.deleted_at.map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default());
+                        ui.horizontal(|ui| {
+                            if ui.button("Restore").clicked() {
+                                restore = Some(item.clone());
+                            }
+                            if ui.button("Delete Permanently").clicked() {
+                                view.confirm = Some(Purge::One(item.clone()));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    view.open = open;
+
+    if let Some(item) = restore {
+        match trash.0.restore(fs, &item) {
+            Ok(path) => {
+                notices.send(Notice::info(format!("Restored {}", path.display())));
+                // The restored path is absolute, so let the listing decide whether it's in view
+                fs_changes.send(FsChange::Rescan(listing.dir().to_path_buf()));
+            }
+            Err(e) => {
+                notices.send(Notice::error(format!("Couldn't restore {}: {}", item.name(), e)));
+            }
+        }
+        view.items = None;
+    }
+
+    // Nothing leaves the trash for good without a second click
+    let Some(purge) = &view.confirm else {
+        return;
+    };
+    let question = match purge {
+        Purge::One(item) => format!("Permanently delete \"{}\"? This can't be undone.", item.name()),
+        Purge::All => format!("Permanently delete all {} item(s) in the trash? This can't be undone.", items.len()),
+    };
+    let mut decided = None;
+    egui::Window::new("Delete Permanently")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(question);
+            ui.horizontal(|ui| {
+                if ui.button("Delete").clicked() {
+                    decided = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    decided = Some(false);
+                }
+            });
+        });
+
+    if let Some(confirmed) = decided {
+        if let (true, Some(purge)) = (confirmed, view.confirm.take()) {
+            let result = match &purge {
+                Purge::One(item) => trash.0.purge(fs, item),
+                Purge::All => trash.0.empty(fs),
+            };
+            if let Err(e) = result {
+                notices.send(Notice::error(format!("Couldn't delete from the trash: {}", e)));
+            }
+            view.items = None;
+        }
+        view.confirm = None;
+    }
+}
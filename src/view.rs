@@ -0,0 +1,362 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate};
+use file_manager_core::Entry;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::navigation::Navigation;
+use crate::settings::Settings;
+
+// How each folder is shown: as icons or as a details table, in which order, and
+// whether the icons are grouped by type or by date. Remembered per folder with the
+// settings. Ctrl+1 shows icons, Ctrl+2 details.
+pub struct ViewPlugin;
+
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, view_shortcuts);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ViewMode {
+    #[default]
+    Icons,
+    Details,  // One row per item with its size, type, times and permissions
+}
+
+// What the items can be ordered by, one per details column
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Name,  // Natural order, ignoring case: "file2" before "File10"
+    Size,
+    Type,  // By extension, which is what the type shows
+    Modified,
+    Created,
+    Permissions,
+}
+
+// The edge of the grid's icons in pixels, zoomed with Ctrl+scroll or View > Icon Size
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(transparent)]
+pub struct IconSize(f32);
+
+impl IconSize {
+    pub const RANGE: RangeInclusive<f32> = 32.0..=256.0;
+
+    pub fn new(size: f32) -> Self {
+        Self(size.clamp(*Self::RANGE.start(), *Self::RANGE.end()))
+    }
+
+    pub fn get(self) -> f32 {
+        Self::new(self.0).0  // The settings file may have been edited by hand
+    }
+}
+
+impl Default for IconSize {
+    fn default() -> Self {
+        Self(75.0)
+    }
+}
+
+// Every sort key with its title, in the order of the details columns
+pub const SORT_KEYS: [(SortKey, &str); 6] = [
+    (SortKey::Name, "Name"),
+    (SortKey::Size, "Size"),
+    (SortKey::Type, "Type"),
+    (SortKey::Modified, "Modified"),
+    (SortKey::Created, "Created"),
+    (SortKey::Permissions, "Permissions"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self { key: SortKey::Name, descending: false }
+    }
+}
+
+impl Sort {
+    // Orders two entries, keeping folders ahead of files either way
+    pub fn compare(&self, a: &Entry, b: &Entry) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Name => natural_cmp(&a.name(), &b.name()),
+            SortKey::Size => a.len.cmp(&b.len),
+            SortKey::Type => extension(a).cmp(&extension(b)),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+            SortKey::Created => a.created.cmp(&b.created),
+            SortKey::Permissions => a.mode.cmp(&b.mode),
+        }
+        .then_with(|| a.path.cmp(&b.path));  // Ties always come out the same way
+        let ordering = if self.descending { ordering.reverse() } else { ordering };
+        b.is_dir.cmp(&a.is_dir).then(ordering)
+    }
+
+    // The order after clicking the header of `key`: ascending first, then flipped
+    pub fn toggled(self, key: SortKey) -> Sort {
+        Sort { key, descending: self.key == key && !self.descending }
+    }
+}
+
+// Sections the icons are split into, each under its own header
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Grouping {
+    #[default]
+    None,
+    Type,  // Folders, then one section per kind of file
+    Date,  // Today, This week and Older, by modification time
+}
+
+impl Grouping {
+    // The title of the section `entry` goes in, e.g. "Folders", "PNG file" or "This week"
+    pub fn section(self, entry: &Entry, today: NaiveDate) -> Option<String> {
+        match self {
+            Grouping::None => None,
+            Grouping::Type if entry.is_dir => Some(String::from("Folders")),
+            Grouping::Type => Some(type_name(entry)),
+            Grouping::Date => Some(String::from(["Today", "This week", "Older"][usize::from(date_bucket(entry, today))])),
+        }
+    }
+
+    // Orders the sections: folders ahead of the file types, newest first for dates
+    pub fn compare(self, a: &Entry, b: &Entry, today: NaiveDate) -> Ordering {
+        match self {
+            Grouping::None => Ordering::Equal,
+            Grouping::Type => b.is_dir.cmp(&a.is_dir).then_with(|| extension(a).cmp(&extension(b))),
+            Grouping::Date => date_bucket(a, today).cmp(&date_bucket(b, today)),
+        }
+    }
+}
+
+// 0 for today, 1 for earlier this week (since Monday), 2 for older or unknown
+fn date_bucket(entry: &Entry, today: NaiveDate) -> u8 {
+    let Some(modified) = entry.modified else {
+        return 2;
+    };
+    let day = DateTime::<Local>::from(modified).date_naive();
+    let monday = today - Days::new(u64::from(today.weekday().num_days_from_monday()));
+    if day >= today {
+        0  // Including times in the future, from clocks that disagree
+    } else if day >= monday {
+        1
+    } else {
+        2
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct FolderView {
+    pub mode: ViewMode,
+    pub sort: Sort,
+    pub grouping: Grouping,
+}
+
+// The view of every folder that isn't shown the default way
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct FolderViews(BTreeMap<PathBuf, FolderView>);
+
+impl FolderViews {
+    pub fn get(&self, dir: &Path) -> FolderView {
+        self.0.get(dir).copied().unwrap_or_default()
+    }
+
+    fn set(&mut self, dir: &Path, view: FolderView) {
+        if view == FolderView::default() {
+            self.0.remove(dir);
+        } else if dir.to_str().is_some() {
+            self.0.insert(dir.to_path_buf(), view);  // settings.ron can only hold UTF-8 paths
+        }
+    }
+}
+
+// Remembers `view` for `dir`, touching the settings only when it changed so they aren't saved every frame
+pub fn remember(settings: &mut ResMut<Settings>, dir: &Path, view: FolderView) {
+    if settings.folder_views.get(dir) != view {
+        settings.folder_views.set(dir, view);
+    }
+}
+
+// Compares names the way people count: case is ignored, and runs of digits are
+// compared as numbers, so "page2" comes before "Page10"
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let (x, y) = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => (*x, *y),
+        };
+        let ordering = if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (x, y) = (digits(&mut a), digits(&mut b));
+            let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+            // Longer means bigger once leading zeros are gone; "01" goes after "1"
+            x_value.len().cmp(&y_value.len()).then_with(|| x_value.cmp(y_value)).then_with(|| x.len().cmp(&y.len()))
+        } else {
+            a.next();
+            b.next();
+            x.to_lowercase().cmp(y.to_lowercase())
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+// The extension, ignoring case, or "" for folders and files without one
+fn extension(entry: &Entry) -> String {
+    match entry.path.extension() {
+        Some(extension) if !entry.is_dir => extension.to_string_lossy().to_lowercase(),
+        _ => String::new(),
+    }
+}
+
+// "Folder", or the extension in capitals, e.g. "PNG file"
+pub fn type_name(entry: &Entry) -> String {
+    if entry.is_dir {
+        return String::from("Folder");
+    }
+    match entry.path.extension() {
+        Some(extension) => format!("{} file", extension.to_string_lossy().to_uppercase()),
+        None => String::from("File"),
+    }
+}
+
+// Sizes in bytes, KB, MB or GB, e.g. "1.4 MB"
+pub fn size_text(len: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = len as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", len, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+pub fn time_text(time: Option<SystemTime>) -> String {
+    time.map(|time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default()
+}
+
+// Like `ls -l`, e.g. "rwxr-xr-x"
+pub fn permissions_text(mode: Option<u32>) -> String {
+    let Some(mode) = mode else {
+        return String::new();
+    };
+    (0..9)
+        .map(|bit| {
+            let set = mode & (0o400 >> bit) != 0;
+            match (set, bit % 3) {
+                (false, _) => '-',
+                (true, 0) => 'r',
+                (true, 1) => 'w',
+                (true, _) => 'x',
+            }
+        })
+        .collect()
+}
+
+// Ctrl+1 and Ctrl+2 switch the folder being shown to icons or details, unless a text field wants the keys
+fn view_shortcuts(mut contexts: EguiContexts, navigation: Res<Navigation>, mut settings: ResMut<Settings>) {
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+    let (icons, details) = ctx.input_mut(|i| {
+        (i.consume_key(egui::Modifiers::COMMAND, egui::Key::Num1), i.consume_key(egui::Modifiers::COMMAND, egui::Key::Num2))
+    });
+    let mode = match (icons, details) {
+        (true, _) => ViewMode::Icons,
+        (_, true) => ViewMode::Details,
+        _ => return,
+    };
+    let dir = navigation.current();
+    let view = FolderView { mode, ..settings.folder_views.get(dir) };
+    remember(&mut settings, dir, view);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, is_dir: bool) -> Entry {
+        Entry { path: PathBuf::from(path), is_dir, is_symlink: false, len: 0, modified: None, created: None, mode: None }
+    }
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<&str> = names.to_vec();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names.into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn numbers_are_compared_by_value() {
+        assert_eq!(sorted(&["file10", "file2", "file1"]), ["file1", "file2", "file10"]);
+        assert_eq!(natural_cmp("v1.10", "v1.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn leading_zeros_only_break_ties() {
+        assert_eq!(sorted(&["007", "8", "10", "7"]), ["7", "007", "8", "10"]);
+        assert_eq!(natural_cmp("a00", "a0"), Ordering::Greater);
+        assert_eq!(natural_cmp("photo 001", "photo 1"), Ordering::Greater);
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(sorted(&["banana", "Apple", "cherry"]), ["Apple", "banana", "cherry"]);
+        assert_eq!(natural_cmp("README", "readme"), Ordering::Equal);
+        assert_eq!(natural_cmp("File10", "file2"), Ordering::Greater);
+    }
+
+    #[test]
+    fn digits_at_the_end_of_a_name() {
+        assert_eq!(sorted(&["notes12", "notes", "notes3"]), ["notes", "notes3", "notes12"]);
+        assert_eq!(natural_cmp("track9", "track 9"), Ordering::Greater);  // A space goes before any digit
+    }
+
+    #[test]
+    fn folders_stay_first_either_way() {
+        let (folder, a, b) = (entry("/x/zzz", true), entry("/x/a2", false), entry("/x/A10", false));
+        let ascending = Sort::default();
+        let descending = ascending.toggled(SortKey::Name);
+        assert!(descending.descending);
+
+        assert_eq!(ascending.compare(&folder, &a), Ordering::Less);
+        assert_eq!(descending.compare(&folder, &a), Ordering::Less);
+        assert_eq!(ascending.compare(&a, &b), Ordering::Less);
+        assert_eq!(descending.compare(&a, &b), Ordering::Greater);
+    }
+
+    #[test]
+    fn names_differing_only_in_case_still_have_an_order() {
+        let (upper, lower) = (entry("/x/Notes", false), entry("/x/notes", false));
+        let sort = Sort::default();
+        assert_eq!(sort.compare(&upper, &lower), Ordering::Less);
+        assert_eq!(sort.compare(&lower, &upper), Ordering::Greater);
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+
+use crate::commander::Commander;
+use crate::listing::DirectoryListing;
+use crate::notifications::Notice;
+use crate::tabs::Tabs;
+use crate::Storage;
+
+// Watches the folders on screen (inotify on Linux, the native backend elsewhere)
+// and turns what other programs do to them into `FsChange` events.
+pub struct WatcherPlugin;
+
+impl Plugin for WatcherPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FsChange>();
+        match DirWatcher::new() {
+            Ok(watcher) => {
+                app.insert_resource(watcher)
+                    .add_systems(PreUpdate, (sync_watches, forward_events).chain());
+            }
+            // Without a watcher the listing still updates after our own file operations
+            Err(e) => {
+                app.add_event::<Notice>()
+                    .world_mut()
+                    .send_event(Notice::error(format!("Couldn't watch folders for changes: {}", e)));
+            }
+        }
+    }
+}
+
+// Something changed inside a watched folder. Paths are spelled relative to the
+// folder as the UI asked for it (e.g. `./root/a.txt`), so they match listing entries.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub enum FsChange {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+    Renamed { from: PathBuf, to: PathBuf },
+    Rescan(PathBuf),  // Events were lost or the folder itself moved, it has to be read again
+}
+
+#[derive(Resource)]
+pub struct DirWatcher {
+    watcher: RecommendedWatcher,
+    events: Mutex<Receiver<notify::Result<notify::Event>>>,  // Filled by the watcher's own thread
+    watched: Vec<(PathBuf, PathBuf)>,  // (folder as the UI spells it, absolute folder as reported by events)
+}
+
+impl DirWatcher {
+    fn new() -> notify::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender)?;
+        Ok(Self {
+            watcher,
+            events: Mutex::new(events),
+            watched: Vec::new(),
+        })
+    }
+
+    // Makes the watched folders exactly `dirs`, adding and dropping watches as needed
+    pub fn set_watched(&mut self, dirs: &[PathBuf]) {
+        let (keep, drop): (Vec<_>, Vec<_>) = std::mem::take(&mut self.watched)
+            .into_iter()
+            .partition(|(dir, _)| dirs.contains(dir));
+        for (_, absolute) in drop {
+            let _ = self.watcher.unwatch(&absolute);  // The folder may already be gone
+        }
+        self.watched = keep;
+
+        for dir in dirs {
+            if self.watched.iter().any(|(watched, _)| watched == dir) {
+                continue;
+            }
+            let Ok(absolute) = std::path::absolute(dir) else {
+                continue;
+            };
+            // Half-typed or deleted paths can't be watched; they're retried when the folder list changes
+            if self.watcher.watch(&absolute, RecursiveMode::NonRecursive).is_ok() {
+                self.watched.push((dir.clone(), absolute));
+            }
+        }
+    }
+
+    fn is_watching_exactly(&self, dirs: &[PathBuf]) -> bool {
+        self.watched.len() == dirs.len() && self.watched.iter().all(|(dir, _)| dirs.contains(dir))
+    }
+
+    // Maps a path from an event back to the spelling used by the UI
+    fn to_ui_path(&self, path: &Path) -> Option<PathBuf> {
+        let parent = path.parent()?;
+        let (dir, _) = self.watched.iter().find(|(_, absolute)| absolute == parent)?;
+        Some(dir.join(path.file_name()?))
+    }
+
+    // Same, for events about a watched folder itself
+    fn to_ui_dir(&self, path: &Path) -> Option<PathBuf> {
+        let (dir, _) = self.watched.iter().find(|(_, absolute)| absolute == path)?;
+        Some(dir.clone())
+    }
+}
+
+// Keeps the watch list in step with the folders open in any tab or commander pane.
+// Tabs in the background are watched too, their listings are patched from the events.
+fn sync_watches(listing: Res<DirectoryListing>, tabs: Res<Tabs>, commander: Res<Commander>, mut watcher: ResMut<DirWatcher>) {
+    let mut dirs: Vec<PathBuf> = tabs.inactive().map(|(_, tab)| tab.dir().to_path_buf()).collect();
+    dirs.push(listing.dir().to_path_buf());
+    dirs.extend(commander.other_dir().map(Path::to_path_buf));
+    dirs.sort();
+    dirs.dedup();  // Two tabs showing the same folder need only one watch
+    if !watcher.is_watching_exactly(&dirs) {
+        watcher.set_watched(&dirs);
+    }
+}
+
+// Drains the watcher's channel and translates what it reports into `FsChange`s
+fn forward_events(
+    storage: Res<Storage>,
+    watcher: Res<DirWatcher>,
+    mut changes: EventWriter<FsChange>,
+    mut notices: EventWriter<Notice>,
+) {
+    let Ok(events) = watcher.events.lock() else {
+        return;
+    };
+
+    for event in events.try_iter() {
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                notices.send(Notice::error(format!("Error watching folder: {}", e)));
+                continue;
+            }
+        };
+
+        if event.need_rescan() {
+            changes.send_batch(watcher.watched.iter().map(|(dir, _)| FsChange::Rescan(dir.clone())));
+            continue;
+        }
+
+        // The watched folder itself moved or vanished; re-reading it will surface the error
+        if matches!(event.kind, EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))) {
+            let moved: Vec<PathBuf> = event.paths.iter().filter_map(|path| watcher.to_ui_dir(path)).collect();
+            if !moved.is_empty() {
+                changes.send_batch(moved.into_iter().map(FsChange::Rescan));
+                continue;
+            }
+        }
+
+        let paths: Vec<PathBuf> = event
+            .paths
+            .iter()
+            .filter_map(|path| watcher.to_ui_path(path))
+            .collect();
+
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                // Either side can be outside the watched folders, which makes it a plain create or delete
+                let from = event.paths.first().and_then(|path| watcher.to_ui_path(path));
+                let to = event.paths.get(1).and_then(|path| watcher.to_ui_path(path));
+                match (from, to) {
+                    (Some(from), Some(to)) => {
+                        changes.send(FsChange::Renamed { from, to });
+                    }
+                    (Some(from), None) => {
+                        changes.send(FsChange::Removed(from));
+                    }
+                    (None, Some(to)) => {
+                        changes.send(FsChange::Created(to));
+                    }
+                    (None, None) => {}
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+                changes.send_batch(paths.into_iter().map(FsChange::Removed));
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) | EventKind::Create(_) => {
+                changes.send_batch(paths.into_iter().map(FsChange::Created));
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // The backend couldn't tell which side of the rename this is, so look (through the jail)
+                for path in paths {
+                    if storage.0.metadata(&path).is_ok() {
+                        changes.send(FsChange::Created(path));
+                    } else {
+                        changes.send(FsChange::Removed(path));
+                    }
+                }
+            }
+            EventKind::Modify(_) => {
+                changes.send_batch(paths.into_iter().map(FsChange::Modified));
+            }
+            _ => {}  // Access events and the like don't change the listing
+        }
+    }
+}